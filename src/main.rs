@@ -1,19 +1,30 @@
 use base64::{engine::general_purpose, Engine};
 use clap::{CommandFactory, Parser, Subcommand};
+use qrgen::utils::{
+    generate::DrawItem,
+    payload::Payload,
+    template::{Position, Template, ValueTemplate, Variables},
+    validate::Issue,
+};
 use rayon::prelude::*;
 use rust_text_draw::{
     fontdb::{self},
     image::ImageFormat,
-    Widget,
 };
 use serde_json::{from_value, Value};
 use std::{
     collections::HashMap,
     fs::{create_dir_all, read},
-    io::Cursor,
+    io::{Cursor, Write},
     path::Path,
+    sync::{Arc, Mutex},
 };
 
+// Path to read input from stdin or output directory to write to stdout
+const STDIO: &str = "-";
+// Max count of numbers of `seq`
+const SEQ_MAX: usize = 1_000_000;
+
 static FONT_DEFAULT: &'static [u8] = include_bytes!("../fonts/poppins-v21-latin-regular.ttf");
 
 /// QR Code Generator and Draws Tools
@@ -29,13 +40,23 @@ struct Args {
 }
 
 #[derive(Subcommand, Debug, serde::Deserialize)]
+#[allow(clippy::large_enum_variant)]
 enum Command {
     /// Generate one QR code
     Gen(GenArg),
 
-    /// Generate multiple QR codes from a CSV file
+    /// Generate multiple QR codes from a CSV, TSV, JSON, JSON Lines or spreadsheet file
     From(FromArg),
 
+    /// Generate multiple QR codes from a range of numbers, without a data file
+    Seq(SeqArg),
+
+    /// Read QR codes from image files
+    Decode(DecodeArg),
+
+    /// Check config file, draw template and data before generating
+    Validate(Config),
+
     /// Run command from config file
     Config(Config),
 }
@@ -86,7 +107,7 @@ struct CommonArg {
     /// Draw template (json5) (ignored from clap)
     #[clap(skip)]
     #[serde(skip)]
-    template_draw: Option<Vec<Widget>>,
+    template_draw: Option<Vec<DrawItem>>,
 
     /// Draw template as string (json5)
     #[clap(short = 'd', long = "td")]
@@ -104,13 +125,37 @@ struct CommonArg {
     #[clap(long = "rfs", default_value = "0", hide = true)]
     reduce_font_size: u32,
 
-    /// Output directory
+    /// Output directory, `-` to write to stdout
     #[clap(short = 'o', long, default_value = "output")]
     outdir: String,
 
     /// QR error correction level (l|m|q|h)
     #[clap(long = "ecc", default_value = "m")]
     error_correction_level: String,
+
+    /// Build QR content from payload fields instead of content (wifi|vcard|mecard|promptpay|emvco|epc|swiss_qr_bill|event|geo|mailto|sms|tel|totp|hotp|bitcoin|ethereum|gs1|gs1_datamatrix|gs1_digital_link)
+    #[clap(long = "payload")]
+    payload: Option<String>,
+
+    /// Payload field as key=value, repeatable (also works with data template)
+    #[clap(long = "pf")]
+    payload_field: Vec<String>,
+
+    /// Path to CSV file to save values generated by payload (e.g. OTP secret)
+    #[clap(long = "sidecar")]
+    sidecar: Option<String>,
+
+    /// Compress and encode content to keep QR small, e.g. zlib+base45 (like EU DCC), zstd+base64url ([deflate|zlib|zstd+]base45|base64url)
+    #[clap(long = "pack")]
+    pack: Option<String>,
+
+    /// Sign content and append signature as `content.signature` (before --pack), algorithm:key with key as file path or `env.NAME`, e.g. ed25519:private.pem, hmac:env.QR_KEY
+    #[clap(long = "sign")]
+    sign: Option<String>,
+
+    /// Variables for `{{vars.name}}` in templates, from `vars` of config file (ignored from clap)
+    #[clap(skip)]
+    vars: Variables,
 }
 
 impl Default for CommonArg {
@@ -133,18 +178,25 @@ impl Default for CommonArg {
             outdir: "output".to_string(),
             error_correction_level: "m".to_string(),
             template_draw_string: None,
+            payload: None,
+            payload_field: Vec::new(),
+            sidecar: None,
+            pack: None,
+            sign: None,
+            vars: Variables::new(),
         }
     }
 }
 
 #[derive(Parser, Debug, serde::Deserialize)]
 #[command(
-    after_help = "Template can use `{{Number of column}}` to replace column data, and `{{ROW}}` to replace row number."
+    after_help = "Template can use `{{Number of column}}` (or `{{Column name}}` with --header) to replace column data, and `{{ROW}}` to replace row number. Filters: `{{ 1 | trim | upper }}` (see README)."
 )]
 #[serde(default)]
 struct FromArg {
-    /// Path to CSV file
-    path: String,
+    /// Path to data file (csv|tsv|json|jsonl|xlsx|ods), `-` to read from stdin
+    #[clap(required_unless_present = "sqlite")]
+    path: Option<String>,
 
     /// QR content template
     #[clap(short = 'c', long = "tc", default_value = "{{1}}")]
@@ -154,6 +206,58 @@ struct FromArg {
     #[clap(short = 'n', long = "tfn", default_value = "{{1}}")]
     template_filename: String,
 
+    /// First row of CSV or spreadsheet is header, template can use column name like `{{email}}`
+    #[clap(long = "header")]
+    header: bool,
+
+    /// Input format (csv|tsv|json|jsonl|xlsx|ods) (default: by file extension)
+    #[clap(long = "input-format")]
+    input_format: Option<String>,
+
+    /// CSV field delimiter (`\t` for tab) (default: `,`, tab for tsv)
+    #[clap(long = "delimiter")]
+    delimiter: Option<String>,
+
+    /// CSV quote character (default: `"`)
+    #[clap(long = "quote")]
+    quote: Option<String>,
+
+    /// Input text encoding, e.g. windows-1252, shift_jis, utf-16le (default: utf-8)
+    #[clap(long = "encoding")]
+    encoding: Option<String>,
+
+    /// Spreadsheet sheet name or number (start at 1) (default: first sheet)
+    #[clap(long = "sheet")]
+    sheet: Option<String>,
+
+    /// Spreadsheet cell range, e.g. A2:D100, or B3 to the end (default: used cells)
+    #[clap(long = "range")]
+    range: Option<String>,
+
+    /// Path to SQLite database, rows from --query (column names can be used in template)
+    #[clap(long = "sqlite", requires = "query", conflicts_with = "path")]
+    sqlite: Option<String>,
+
+    /// SQL query for --sqlite
+    #[clap(long = "query", requires = "sqlite")]
+    query: Option<String>,
+
+    /// Number of rows (CSV records) to skip before header (or data) of CSV or spreadsheet
+    #[clap(long = "skip", default_value = "0")]
+    skip: usize,
+
+    /// Rows to process (start at 1, after header), e.g. 10-50, 1,3,7-9 or 100-
+    #[clap(long = "rows")]
+    rows: Option<String>,
+
+    /// Process rows where column matches, repeatable, e.g. "status == damaged" (==|!=|~=|>|>=|<|<=)
+    #[clap(long = "filter")]
+    filter: Vec<String>,
+
+    /// Process only first N rows (after rows and filter), e.g. for preview
+    #[clap(long = "limit")]
+    limit: Option<usize>,
+
     #[command(flatten)]
     #[serde(default)]
     common_arg: CommonArg,
@@ -165,6 +269,63 @@ impl Default for FromArg {
             path: Default::default(),
             template_content: Some("{{1}}".to_string()),
             template_filename: "{{1}}".to_string(),
+            header: false,
+            input_format: None,
+            delimiter: None,
+            quote: None,
+            encoding: None,
+            sheet: None,
+            range: None,
+            sqlite: None,
+            query: None,
+            skip: 0,
+            rows: None,
+            filter: Vec::new(),
+            limit: None,
+            common_arg: Default::default(),
+        }
+    }
+}
+
+#[derive(Parser, Debug, serde::Deserialize)]
+#[command(
+    after_help = "Template can use `{{ n }}` to replace number, `{{ n:05 }}` to pad with zeros to 5 digits, and filters like `from` (see README)."
+)]
+#[serde(default)]
+struct SeqArg {
+    /// First number
+    #[clap(allow_negative_numbers = true)]
+    start: i64,
+
+    /// Last number (inclusive)
+    #[clap(allow_negative_numbers = true)]
+    end: i64,
+
+    /// Step between numbers, negative to count down
+    #[clap(long = "step", default_value = "1", allow_negative_numbers = true)]
+    step: i64,
+
+    /// QR content template
+    #[clap(short = 'c', long = "tc", default_value = "{{n}}")]
+    template_content: Option<String>,
+
+    /// Filename template
+    #[clap(short = 'n', long = "tfn", default_value = "{{n}}")]
+    template_filename: String,
+
+    #[command(flatten)]
+    #[serde(default)]
+    common_arg: CommonArg,
+}
+
+impl Default for SeqArg {
+    fn default() -> Self {
+        Self {
+            start: 1,
+            end: 1,
+            step: 1,
+            template_content: Some("{{n}}".to_string()),
+            template_filename: "{{n}}".to_string(),
             common_arg: Default::default(),
         }
     }
@@ -189,6 +350,25 @@ impl Default for GenArg {
     }
 }
 
+#[derive(Parser, Debug, serde::Deserialize)]
+struct DecodeArg {
+    /// Image file paths, `-` to read from stdin
+    #[clap(required = true)]
+    paths: Vec<String>,
+
+    /// Verify signature appended by --sign (hmac|ed25519), print content without signature
+    #[clap(long = "verify-signature", requires = "key")]
+    verify_signature: Option<String>,
+
+    /// Key for --verify-signature, file path or `env.NAME` (secret key for hmac, public key for ed25519)
+    #[clap(long = "key", requires = "verify_signature")]
+    key: Option<String>,
+
+    /// Decode and decompress content packed by --pack, e.g. zlib+base45 (before signature check)
+    #[clap(long = "unpack")]
+    unpack: Option<String>,
+}
+
 #[derive(Parser, Debug, serde::Deserialize)]
 struct Config {
     /// Path to the config file
@@ -208,55 +388,221 @@ fn main() {
         return;
     }
 
-    match &args.command {
+    match args.command {
         Command::Config(config) => run_command_from_config_file(&config.path),
-        Command::Gen(state) => handle_gen_command(state),
-        Command::From(state) => handle_from_command(state),
+        Command::Gen(state) => handle_gen_command(&state),
+        Command::From(state) => handle_from_command(&state),
+        Command::Seq(state) => handle_seq_command(state),
+        Command::Decode(state) => {
+            if !handle_decode_command(&state) {
+                std::process::exit(1);
+            }
+        }
+        Command::Validate(config) => {
+            if !handle_validate_command(&config.path) {
+                std::process::exit(1);
+            }
+        }
     }
     let elapsed = now.elapsed();
-    println!("Elapsed: {:.2?}", elapsed);
+    eprintln!("Elapsed: {:.2?}", elapsed);
 }
 
 // Function to execute command from config file
 fn run_command_from_config_file<P: AsRef<Path>>(path: P) {
     let content = std::fs::read_to_string(path).expect("Cannot read config file");
 
+    let (command, state) = match read_config(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: Invalid config file format: {}", e);
+            return;
+        }
+    };
+
+    match command.as_str() {
+        "gen" => {
+            let state: GenArg = from_value(state).expect("Invalid Gen command format");
+            handle_gen_command(&state);
+        }
+        "from" => {
+            let state: FromArg = from_value(state).expect("Invalid From command format");
+            handle_from_command(&state);
+        }
+        "seq" => {
+            let state: SeqArg = from_value(state).expect("Invalid Seq command format");
+            handle_seq_command(state);
+        }
+        _ => {
+            eprintln!("Unsupported command in config file.");
+        }
+    }
+}
+
+// Read command (lowercase) and its arguments from config file content,
+// `vars` are put in `common_arg.vars` for templates
+fn read_config(content: &str) -> Result<(String, Value), String> {
     #[derive(Debug, serde::Deserialize)]
     struct ConfigFile {
         command: String,
+        /// Variables for `{{vars.name}}` in templates
+        #[serde(default)]
+        vars: HashMap<String, Value>,
     }
-    let config: ConfigFile = json5::from_str(&content).expect("Invalid config file format");
+    let config: ConfigFile = json5::from_str(content).map_err(|e| e.to_string())?;
 
-    let mut state: Value = json5::from_str(&content).expect("Invalid command format");
+    let mut state: Value = json5::from_str(content).map_err(|e| e.to_string())?;
+    let state_value = state.clone();
 
-    let state_value: Value =
-        json5::from_str(&content).expect("Invalid JSON5 format in config file");
+    state["common_arg"]["vars"] = config
+        .vars
+        .into_iter()
+        .map(|(name, value)| match value {
+            Value::String(v) => (name, Value::String(v)),
+            v => (name, Value::String(v.to_string())),
+        })
+        .collect::<serde_json::Map<String, Value>>()
+        .into();
 
     // Convert template_draw array to template_draw_string if present
     if state_value["common_arg"]["template_draw"].is_array() {
         state["common_arg"]["template_draw_string"] = Some(
             json5::to_string(&state_value["common_arg"]["template_draw"].clone())
-                .expect("Invalid template draw format"),
+                .map_err(|e| e.to_string())?,
         )
         .into();
     }
 
-    match config.command.to_lowercase().as_str() {
-        "gen" => {
-            let state: GenArg = from_value(state).expect("Invalid Gen command format");
-            handle_gen_command(&state);
-        }
-        "from" => {
-            let state: FromArg = from_value(state).expect("Invalid From command format");
-            handle_from_command(&state);
-        }
-        _ => {
-            eprintln!("Unsupported command in config file.");
+    // Convert payload_field object to list of key=value if present
+    if let Some(fields) = state_value["common_arg"]["payload_field"].as_object() {
+        state["common_arg"]["payload_field"] = fields
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(v) => format!("{}={}", key, v),
+                v => format!("{}={}", key, v),
+            })
+            .collect::<Vec<String>>()
+            .into();
+    }
+
+    Ok((config.command.to_lowercase(), state))
+}
+
+// Build QR content from payload fields when payload is set, field values are resolved by `resolve`
+fn build_payload<F>(common_arg: &CommonArg, resolve: F) -> Option<Result<Payload, String>>
+where
+    F: Fn(&String) -> Result<String, String>,
+{
+    let kind = common_arg.payload.as_ref()?;
+    let result =
+        qrgen::utils::payload::parse_fields(&common_arg.payload_field).and_then(|fields| {
+            let fields = fields
+                .into_iter()
+                .map(|(k, v)| Ok((k, resolve(&v)?)))
+                .collect::<Result<_, String>>()?;
+            qrgen::utils::payload::build(kind, &fields)
+        });
+    Some(result)
+}
+
+// Signer of --sign `algorithm:key`, key is read once for all rows
+fn parse_sign(value: &str) -> Result<Arc<qrgen::utils::sign::Signer>, String> {
+    let (algorithm, key) = value.split_once(':').ok_or(format!(
+        "Invalid --sign: \"{}\" (algorithm:key, e.g. ed25519:private.pem)",
+        value
+    ))?;
+    qrgen::utils::sign::signer(algorithm, key)
+}
+
+// QR versions (before, after) of packed content, None when content is too long
+type PackVersions = (Option<u8>, Option<u8>);
+
+// Sign payload content by --sign, then pack it by --pack, returns QR versions when packed
+fn pack_payload(
+    common_arg: &CommonArg,
+    payload: &mut Payload,
+) -> Result<Option<PackVersions>, String> {
+    if let Some(value) = &common_arg.sign {
+        if payload.gs1 {
+            return Err("--sign cannot be used with GS1 payload".to_string());
         }
+        payload.content = parse_sign(value)?.sign(&payload.content);
+    }
+    let Some(method) = &common_arg.pack else {
+        return Ok(None);
+    };
+    if payload.gs1 {
+        return Err("--pack cannot be used with GS1 payload".to_string());
     }
+    let ecc = qrgen::utils::qr::parse_ecc(
+        payload
+            .error_correction_level
+            .as_deref()
+            .unwrap_or(&common_arg.error_correction_level),
+    );
+    let before = qrgen::utils::qr::version(&payload.content, ecc);
+    payload.content = qrgen::utils::pack::pack(&payload.content, method)?;
+    Ok(Some((
+        before,
+        qrgen::utils::qr::version(&payload.content, ecc),
+    )))
+}
+
+// Report QR version saved by --pack, largest version of all codes
+fn report_pack(versions: &[PackVersions]) {
+    let largest =
+        |versions: Vec<Option<u8>>| versions.into_iter().max_by_key(|v| v.unwrap_or(u8::MAX));
+    let text = |version: Option<Option<u8>>| match version {
+        Some(Some(v)) => v.to_string(),
+        _ => "too long".to_string(),
+    };
+    if versions.is_empty() {
+        return;
+    }
+    eprintln!(
+        "Pack: QR version {} to {}{}",
+        text(largest(versions.iter().map(|v| v.0).collect())),
+        text(largest(versions.iter().map(|v| v.1).collect())),
+        if versions.len() > 1 {
+            format!(" (largest of {} codes)", versions.len())
+        } else {
+            String::new()
+        }
+    );
 }
 
 fn handle_gen_command(gen_opt: &GenArg) {
+    let mut payload = match build_payload(&gen_opt.common_arg, |v| Ok(v.clone())) {
+        Some(Ok(payload)) => Some(payload),
+        Some(Err(e)) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+        None => gen_opt.content.clone().map(Payload::from),
+    };
+    if let Some(payload) = payload.as_mut() {
+        match pack_payload(&gen_opt.common_arg, payload) {
+            Ok(versions) => report_pack(&versions.into_iter().collect::<Vec<_>>()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return;
+            }
+        }
+    }
+    let content = payload.as_ref().map(|p| p.content.clone());
+
+    if let (Some(path), Some(payload)) = (&gen_opt.common_arg.sidecar, &payload) {
+        let result = Sidecar::open(path).and_then(|mut sidecar| {
+            sidecar.append(1, &payload.generated)?;
+            sidecar.report();
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    }
+
     let font_db = get_font_db(gen_opt.common_arg.font_path.clone());
 
     let gen_image_opt = qrgen::utils::generate::GenerateImageOptions {
@@ -277,46 +623,51 @@ fn handle_gen_command(gen_opt: &GenArg) {
             .unwrap_or(gen_opt.common_arg.image_width),
         pos_qr_x: gen_opt.common_arg.pos_qr_x,
         pos_qr_y: gen_opt.common_arg.pos_qr_y,
-        error_correction_level: gen_opt.common_arg.error_correction_level.clone(),
-        template_draw: if gen_opt.common_arg.template_draw_string.is_some() {
-            Some(
-                json5::from_str(&gen_opt.common_arg.template_draw_string.as_ref().unwrap())
-                    .expect("Invalid template draw format"),
-            )
-        } else {
-            None
+        error_correction_level: payload
+            .as_ref()
+            .and_then(|p| p.error_correction_level.clone())
+            .unwrap_or(gen_opt.common_arg.error_correction_level.clone()),
+        template_draw: match &gen_opt.common_arg.template_draw_string {
+            Some(t) => match json5::from_str(t) {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    eprintln!("Error: Invalid template draw format: {}", e);
+                    return;
+                }
+            },
+            None => None,
         },
         font_size: gen_opt.common_arg.font_size,
         reduce_font_size: gen_opt.common_arg.reduce_font_size,
         font_db,
+        swiss_cross: payload.as_ref().is_some_and(|p| p.swiss_cross),
+        gs1: payload.as_ref().is_some_and(|p| p.gs1),
+        datamatrix: payload.as_ref().is_some_and(|p| p.datamatrix),
     };
 
     match gen_opt.common_arg.format.as_str() {
-        "console" => qrgen::utils::console::print_qr(if let Some(content) = &gen_opt.content {
-            content
-        } else {
-            eprintln!("Content is required for console output.");
-            return;
-        }),
+        "console" => match payload.as_ref().map(qrgen::utils::console::print_payload) {
+            Some(Ok(_)) => {}
+            Some(Err(e)) => eprintln!("Error: {}", e),
+            None => eprintln!("Content is required for console output."),
+        },
         "png" => {
-            let _ = create_dir_all(&gen_opt.common_arg.outdir.to_string())
-                .expect("Cannot create output directory!");
+            let path = if gen_opt.common_arg.outdir == STDIO {
+                STDIO.to_string()
+            } else {
+                let _ = create_dir_all(&gen_opt.common_arg.outdir.to_string())
+                    .expect("Cannot create output directory!");
+                format!("{}/{}.png", gen_opt.common_arg.outdir, "qr")
+            };
 
-            println!("Generate Image...");
+            eprintln!("Generate Image...");
 
-            let result =
-                qrgen::utils::generate::generate_image(gen_opt.content.clone(), gen_image_opt);
+            let result = qrgen::utils::generate::generate_image(content, gen_image_opt);
 
-            handler_result_generate_image(
-                1,
-                &result,
-                format!("{}/{}.png", gen_opt.common_arg.outdir, "qr"),
-                false,
-            );
+            handler_result_generate_image(1, &result, path, false);
         }
         "base64" => {
-            let result =
-                qrgen::utils::generate::generate_image(gen_opt.content.clone(), gen_image_opt);
+            let result = qrgen::utils::generate::generate_image(content, gen_image_opt);
 
             handler_result_generate_image(1, &result, "".to_owned(), true);
         }
@@ -325,35 +676,781 @@ fn handle_gen_command(gen_opt: &GenArg) {
 }
 
 fn handle_from_command(from_opt: &FromArg) {
-    let list_data =
-        qrgen::utils::process_file::csv_to_vec(&from_opt.path).expect("Error processing file");
+    let read_opt = qrgen::utils::process_file::ReadOptions {
+        format: from_opt.input_format.clone(),
+        delimiter: from_opt.delimiter.clone(),
+        quote: from_opt.quote.clone(),
+        encoding: from_opt.encoding.clone(),
+        sheet: from_opt.sheet.clone(),
+        range: from_opt.range.clone(),
+        header: from_opt.header,
+        skip: from_opt.skip,
+    };
+    let result_table = match (&from_opt.sqlite, &from_opt.query, &from_opt.path) {
+        (Some(db), Some(query), _) => qrgen::utils::process_file::sqlite_to_table(db, query),
+        (Some(_), None, _) => Err("--query is required for --sqlite".to_string()),
+        (None, _, Some(path)) => qrgen::utils::process_file::read_table(path, &read_opt),
+        (None, _, None) => Err("Path to data file is required".to_string()),
+    };
+    match result_table {
+        Ok(table) => generate_from_table(table.headers, table.rows, from_opt),
+        Err(e) => eprintln!("Error processing file: {}", e),
+    }
+}
+
+// Print contents of QR codes, false when any image or signature fails
+fn handle_decode_command(decode_opt: &DecodeArg) -> bool {
+    let verifier = match (&decode_opt.verify_signature, &decode_opt.key) {
+        (Some(algorithm), Some(key)) => match qrgen::utils::sign::Verifier::new(algorithm, key) {
+            Ok(verifier) => Some(verifier),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return false;
+            }
+        },
+        _ => None,
+    };
+    let mut success = true;
+    for path in &decode_opt.paths {
+        let contents = match qrgen::utils::decode::decode_image(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("Error: {} > {}", path, e);
+                success = false;
+                continue;
+            }
+        };
+
+        for content in contents {
+            let content = match &decode_opt.unpack {
+                Some(method) => match qrgen::utils::pack::unpack(&content, method) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        eprintln!("Error: {} > {}", path, e);
+                        success = false;
+                        continue;
+                    }
+                },
+                None => content,
+            };
+            let Some(verifier) = &verifier else {
+                println!("{}", content);
+                continue;
+            };
+            match verifier.verify(&content) {
+                Ok(content) => {
+                    eprintln!("Valid signature: {}", path);
+                    println!("{}", content);
+                }
+                Err(e) => {
+                    eprintln!("Error: {} > {}", path, e);
+                    success = false;
+                }
+            }
+        }
+    }
+    success
+}
+
+// Print problems of config file with line and column, false when there is any error
+fn handle_validate_command(path: &str) -> bool {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Error: Cannot read config file \"{}\": {}", path, e);
+            return false;
+        }
+    };
+
+    // Syntax error has position from parser
+    if let Err(json5::Error::Message { msg, location }) = json5::from_str::<Value>(&content) {
+        let (line, column) = location.map_or((1, 1), |l| (l.line, l.column));
+        eprintln!(
+            "{}:{}:{}: error: Invalid JSON5\n{}",
+            path, line, column, msg
+        );
+        return false;
+    }
+
+    let positions = qrgen::utils::validate::locate_values(&content);
+    let mut issues: Vec<((usize, usize), Issue)> = validate_config(&content)
+        .into_iter()
+        .map(|issue| {
+            let position = qrgen::utils::validate::position(&positions, &issue.path);
+            (position.unwrap_or((1, 1)), issue)
+        })
+        .collect();
+    issues.sort_by_key(|(position, _)| *position);
+
+    for ((line, column), issue) in &issues {
+        eprintln!(
+            "{}:{}:{}: {}: {}{}",
+            path,
+            line,
+            column,
+            if issue.warning { "warning" } else { "error" },
+            issue.message,
+            if issue.path.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", issue.path)
+            }
+        );
+    }
 
-    println!("Generate Images...");
+    let count_error = issues.iter().filter(|(_, i)| !i.warning).count();
+    eprintln!(
+        "Errors: {}, Warnings: {}",
+        count_error,
+        issues.len() - count_error
+    );
+    count_error == 0
+}
+
+// Check arguments, data and templates of config file
+fn validate_config(content: &str) -> Vec<Issue> {
+    let (command, state) = match read_config(content) {
+        Ok(config) => config,
+        Err(e) => return vec![Issue::error("command", e)],
+    };
+    // Keys as written in config, before conversion to arguments
+    let written: Value = json5::from_str(content).unwrap_or_default();
+    let draw_path = if written["common_arg"]["template_draw"].is_array() {
+        "common_arg.template_draw"
+    } else {
+        "common_arg.template_draw_string"
+    };
+    let payload_field_object = written["common_arg"]["payload_field"].is_object();
+
+    fn parse<T: serde::de::DeserializeOwned>(state: Value, issues: &mut Vec<Issue>) -> Option<T> {
+        serde_path_to_error::deserialize(state)
+            .map_err(|e| {
+                let path = e.path().to_string();
+                let path = if path == "." { String::new() } else { path };
+                issues.push(Issue::error(&path, e.into_inner().to_string()))
+            })
+            .ok()
+    }
+
+    let mut issues = Vec::new();
+    match command.as_str() {
+        "gen" => {
+            if let Some(gen_opt) = parse::<GenArg>(state, &mut issues) {
+                if let Some(Err(e)) = build_payload(&gen_opt.common_arg, |v| Ok(v.clone())) {
+                    issues.push(Issue::error("common_arg.payload_field", e));
+                }
+                validate_common_arg(&gen_opt.common_arg, draw_path, None, &mut issues);
+            }
+        }
+        "from" => {
+            if let Some(from_opt) = parse::<FromArg>(state, &mut issues) {
+                let columns = validate_from_data(&from_opt, &mut issues);
+                if let Some((headers, count_col)) = &columns {
+                    let templates = [
+                        ("template_content", from_opt.template_content.as_ref()),
+                        ("template_filename", Some(&from_opt.template_filename)),
+                        (
+                            "common_arg.base_image",
+                            from_opt.common_arg.base_image.as_ref(),
+                        ),
+                    ];
+                    validate_templates(
+                        &templates,
+                        &from_opt.common_arg,
+                        payload_field_object,
+                        headers,
+                        *count_col,
+                        &mut issues,
+                    );
+                }
+                let columns = columns.as_ref().map(|(h, c)| (h.as_slice(), *c));
+                validate_common_arg(&from_opt.common_arg, draw_path, columns, &mut issues);
+            }
+        }
+        "seq" => {
+            if let Some(seq_opt) = parse::<SeqArg>(state, &mut issues) {
+                if seq_opt.step == 0 {
+                    issues.push(Issue::error("step", "Step must not be 0".to_string()));
+                }
+                let headers = vec!["n".to_string()];
+                let templates = [
+                    ("template_content", seq_opt.template_content.as_ref()),
+                    ("template_filename", Some(&seq_opt.template_filename)),
+                    (
+                        "common_arg.base_image",
+                        seq_opt.common_arg.base_image.as_ref(),
+                    ),
+                ];
+                validate_templates(
+                    &templates,
+                    &seq_opt.common_arg,
+                    payload_field_object,
+                    &headers,
+                    1,
+                    &mut issues,
+                );
+                validate_common_arg(
+                    &seq_opt.common_arg,
+                    draw_path,
+                    Some((&headers, 1)),
+                    &mut issues,
+                );
+            }
+        }
+        v => issues.push(Issue::error(
+            "command",
+            format!("Unsupported command: \"{}\" (gen|from|seq)", v),
+        )),
+    }
+    issues
+}
+
+// Read data of from command and check row selection, returns headers and number of columns
+fn validate_from_data(from_opt: &FromArg, issues: &mut Vec<Issue>) -> Option<(Vec<String>, usize)> {
+    let read_opt = qrgen::utils::process_file::ReadOptions {
+        format: from_opt.input_format.clone(),
+        delimiter: from_opt.delimiter.clone(),
+        quote: from_opt.quote.clone(),
+        encoding: from_opt.encoding.clone(),
+        sheet: from_opt.sheet.clone(),
+        range: from_opt.range.clone(),
+        header: from_opt.header,
+        skip: from_opt.skip,
+    };
+    let (path, result_table) = match (&from_opt.sqlite, &from_opt.query, &from_opt.path) {
+        (Some(db), Some(query), _) => (
+            "query",
+            qrgen::utils::process_file::sqlite_to_table(db, query),
+        ),
+        (Some(_), None, _) => ("sqlite", Err("query is required for sqlite".to_string())),
+        (None, _, Some(path)) if path == STDIO => {
+            issues.push(Issue::warning(
+                "path",
+                "Data from stdin is not checked".to_string(),
+            ));
+            return None;
+        }
+        (None, _, Some(path)) => (
+            "path",
+            qrgen::utils::process_file::read_table(path, &read_opt),
+        ),
+        (None, _, None) => ("path", Err("Path to data file is required".to_string())),
+    };
+    let table = match result_table {
+        Ok(table) => table,
+        Err(e) => {
+            issues.push(Issue::error(path, e));
+            return None;
+        }
+    };
+
+    // Check rows and filters separately to report the right key
+    let selections = [
+        (
+            "rows",
+            qrgen::utils::select::RowSelection {
+                rows: from_opt.rows.clone(),
+                ..Default::default()
+            },
+        ),
+        (
+            "filter",
+            qrgen::utils::select::RowSelection {
+                filters: from_opt.filter.clone(),
+                ..Default::default()
+            },
+        ),
+    ];
+    for (key, selection) in selections {
+        if let Err(e) = qrgen::utils::select::select_rows(&table.rows, &table.headers, &selection) {
+            issues.push(Issue::error(key, e));
+        }
+    }
+
+    let count_col = table
+        .rows
+        .first()
+        .map_or(0, |row| row.len())
+        .max(table.headers.len());
+    Some((table.headers, count_col))
+}
+
+// Check placeholders of templates and payload fields
+fn validate_templates(
+    templates: &[(&str, Option<&String>)],
+    common_arg: &CommonArg,
+    payload_field_object: bool,
+    headers: &[String],
+    count_col: usize,
+    issues: &mut Vec<Issue>,
+) {
+    for (path, template) in templates {
+        if let Some(Err(e)) = template
+            .map(|t| qrgen::utils::template::check_columns(t, headers, count_col, &common_arg.vars))
+        {
+            issues.push(Issue::error(path, e));
+        }
+    }
+    for (index, field) in common_arg.payload_field.iter().enumerate() {
+        if let Err(e) =
+            qrgen::utils::template::check_columns(field, headers, count_col, &common_arg.vars)
+        {
+            // Payload fields in config can be object (key: value) or list of key=value
+            let path = match field.split_once('=') {
+                Some((key, _)) if payload_field_object => {
+                    format!("common_arg.payload_field.{}", key)
+                }
+                _ => format!("common_arg.payload_field[{}]", index),
+            };
+            issues.push(Issue::error(&path, e));
+        }
+    }
+}
+
+// Check options, colors, font files, QR position on canvas and draw template,
+// placeholders of draw template are checked when columns are given
+fn validate_common_arg(
+    common_arg: &CommonArg,
+    draw_path: &str,
+    columns: Option<(&[String], usize)>,
+    issues: &mut Vec<Issue>,
+) {
+    if !matches!(common_arg.format.as_str(), "console" | "png" | "base64") {
+        issues.push(Issue::error(
+            "common_arg.format",
+            format!(
+                "Unsupported format: \"{}\" (console|png|base64)",
+                common_arg.format
+            ),
+        ));
+    }
+    if !matches!(
+        common_arg.error_correction_level.as_str(),
+        "l" | "m" | "q" | "h"
+    ) {
+        issues.push(Issue::error(
+            "common_arg.error_correction_level",
+            format!(
+                "Invalid error correction level: \"{}\" (l|m|q|h)",
+                common_arg.error_correction_level
+            ),
+        ));
+    }
+    if let Some(Err(e)) = common_arg
+        .pack
+        .as_ref()
+        .map(|m| qrgen::utils::pack::pack("", m))
+    {
+        issues.push(Issue::error("common_arg.pack", e));
+    }
+    if let Some(Err(e)) = common_arg.sign.as_deref().map(parse_sign) {
+        issues.push(Issue::error("common_arg.sign", e));
+    }
+
+    let colors = [
+        ("common_arg.qr_color_0", &common_arg.qr_color_0),
+        ("common_arg.qr_color_1", &common_arg.qr_color_1),
+        ("common_arg.fill_color", &common_arg.fill_color),
+    ];
+    for (path, color) in colors {
+        if let Err(e) = qrgen::utils::shape::parse_color(color) {
+            issues.push(Issue::error(path, e));
+        }
+    }
+
+    let font_paths = common_arg.font_path.clone().unwrap_or_default();
+    for (index, path) in font_paths.iter().enumerate() {
+        if let Err(e) = std::fs::metadata(path) {
+            issues.push(Issue::error(
+                &format!("common_arg.font_path[{}]", index),
+                format!("Cannot read font file \"{}\": {}", path, e),
+            ));
+        }
+    }
+
+    // Canvas is base image (when it has no placeholder) or image width and height
+    let base_image = common_arg.base_image.as_ref().filter(|p| !p.contains("{{"));
+    let canvas = match base_image {
+        Some(path) => match rust_text_draw::image::image_dimensions(path) {
+            Ok(size) => Some(size),
+            Err(e) => {
+                issues.push(Issue::error(
+                    "common_arg.base_image",
+                    format!("Cannot open base image \"{}\": {}", path, e),
+                ));
+                None
+            }
+        },
+        None if common_arg.base_image.is_some() => None,
+        None => Some((
+            common_arg.image_width,
+            common_arg.image_height.unwrap_or(common_arg.image_width),
+        )),
+    };
+    let qr_size = common_arg.qr_size.unwrap_or(common_arg.image_width);
+    if let Some((width, height)) = canvas.filter(|_| qr_size > 0) {
+        let sides = [
+            ("common_arg.pos_qr_x", common_arg.pos_qr_x, width, "width"),
+            ("common_arg.pos_qr_y", common_arg.pos_qr_y, height, "height"),
+        ];
+        for (path, position, canvas_size, name) in sides {
+            if position as u64 + qr_size as u64 > canvas_size as u64 {
+                issues.push(Issue::error(
+                    path,
+                    format!(
+                        "QR at {} with size {} is out of canvas {} {}",
+                        position, qr_size, name, canvas_size
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(template) = &common_arg.template_draw_string {
+        match json5::from_str::<Value>(template) {
+            Ok(value) => qrgen::utils::validate::check_draw(
+                &value,
+                draw_path,
+                font_paths.len(),
+                columns,
+                &common_arg.vars,
+                issues,
+            ),
+            Err(e) => issues.push(Issue::error(
+                draw_path,
+                format!("Invalid template draw format: {}", e),
+            )),
+        }
+    }
+}
+
+fn handle_seq_command(seq_opt: SeqArg) {
+    if seq_opt.step == 0 {
+        eprintln!("Error: --step must not be 0");
+        return;
+    }
+
+    // Count of numbers from start to end (inclusive), every number is kept as a row in memory
+    let (start, end, step) = (
+        seq_opt.start as i128,
+        seq_opt.end as i128,
+        seq_opt.step as i128,
+    );
+    let count = if (end - start).signum() == -step.signum() {
+        0
+    } else {
+        (end - start) / step + 1
+    };
+    if count == 0 {
+        eprintln!(
+            "Error: no numbers from {} to {} with step {}",
+            seq_opt.start, seq_opt.end, seq_opt.step
+        );
+        return;
+    }
+    if count > SEQ_MAX as i128 {
+        eprintln!(
+            "Error: {} numbers from {} to {} with step {}, max is {} (split into smaller ranges)",
+            count, seq_opt.start, seq_opt.end, seq_opt.step, SEQ_MAX
+        );
+        return;
+    }
+
+    // Numbers as rows of column `n`
+    let rows: Vec<Vec<String>> = (0..count)
+        .map(|i| vec![(start + i * step).to_string()])
+        .collect();
+
+    let from_opt = FromArg {
+        template_content: seq_opt.template_content,
+        template_filename: seq_opt.template_filename,
+        common_arg: seq_opt.common_arg,
+        ..Default::default()
+    };
+    generate_from_table(vec!["n".to_string()], rows, &from_opt);
+}
+
+// Select rows, check templates and generate by output format
+fn generate_from_table(headers: Vec<String>, rows: Vec<Vec<String>>, from_opt: &FromArg) {
+    // Select rows to process, keep index of row for `{{ROW}}`
+    let selection = qrgen::utils::select::RowSelection {
+        rows: from_opt.rows.clone(),
+        filters: from_opt.filter.clone(),
+        limit: from_opt.limit,
+    };
+    let list_data: Vec<(usize, Vec<String>)> =
+        match qrgen::utils::select::select_rows(&rows, &headers, &selection) {
+            Ok(indexes) => indexes.into_iter().map(|i| (i, rows[i].clone())).collect(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return;
+            }
+        };
+
+    // Check columns used in templates before generating
+    let count_col = rows.first().map_or(0, |row| row.len()).max(headers.len());
+    let common_arg = &from_opt.common_arg;
+    let templates = [
+        from_opt.template_content.as_ref(),
+        Some(&from_opt.template_filename),
+        common_arg.base_image.as_ref(),
+    ];
+    let result_check = templates
+        .into_iter()
+        .flatten()
+        .chain(common_arg.payload_field.iter())
+        .try_for_each(|t| {
+            qrgen::utils::template::check_columns(t, &headers, count_col, &common_arg.vars)
+        })
+        .and_then(|_| match &common_arg.template_draw_string {
+            Some(t) => json5::from_str::<Value>(t)
+                .map_err(|e| format!("Invalid template draw format: {}", e))
+                .and_then(|v| {
+                    qrgen::utils::template::check_value(&v, &headers, count_col, &common_arg.vars)
+                }),
+            None => Ok(()),
+        })
+        .and_then(|_| match &common_arg.pack {
+            Some(method) => qrgen::utils::pack::pack("", method).map(|_| ()),
+            None => Ok(()),
+        })
+        .and_then(|_| match &common_arg.sign {
+            Some(value) => parse_sign(value).map(|_| ()),
+            None => Ok(()),
+        });
+    if let Err(e) = result_check {
+        eprintln!("Error: {}", e);
+        return;
+    }
+
+    // Open sidecar before generating, so no QR is made without its saved values
+    let sidecar = match common_arg.sidecar.as_deref().map(Sidecar::open).transpose() {
+        Ok(sidecar) => sidecar,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    eprintln!("Generate Images...");
 
     match from_opt.common_arg.format.as_str() {
-        "console" => generate_list_console(list_data, from_opt),
-        "png" => generate_list_image(list_data, from_opt, false),
-        "base64" => generate_list_image(list_data, from_opt, true),
+        "console" => generate_list_console(list_data, &headers, from_opt, sidecar),
+        "png" => generate_list_image(list_data, &headers, from_opt, false, sidecar),
+        "base64" => generate_list_image(list_data, &headers, from_opt, true, sidecar),
         _ => {
             eprintln!("Format not found!")
         }
     }
 }
 
-fn generate_list_console(list_data: Vec<Vec<String>>, from_opt: &FromArg) {
-    for (index, row) in list_data.iter().enumerate() {
-        // check content and error if empty
-        if from_opt.template_content.is_none() {
-            eprintln!("Error: template_content is required for console output.");
+// CSV file of generated payload values with run start time and row number, rows are appended
+// and flushed as soon as they are generated, so values are kept even if the run stops
+struct Sidecar {
+    path: String,
+    writer: csv::Writer<std::fs::File>,
+    // Header of existing file, checked before appending to it
+    header: Option<Vec<String>>,
+    run: String,
+    count: usize,
+}
+
+impl Sidecar {
+    fn open(path: &str) -> Result<Self, String> {
+        let error = |e: std::io::Error| format!("Cannot open sidecar file \"{}\": {}", path, e);
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        // File holds secrets, readable by owner only
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(path).map_err(error)?;
+
+        let header = if file.metadata().map_err(error)?.len() > 0 {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .from_path(path)
+                .map_err(|e| format!("Cannot read sidecar file \"{}\": {}", path, e))?;
+            let record = reader
+                .records()
+                .next()
+                .transpose()
+                .map_err(|e| format!("Cannot read sidecar file \"{}\": {}", path, e))?;
+            Some(
+                record
+                    .map(|r| r.iter().map(String::from).collect())
+                    .unwrap_or_default(),
+            )
+        } else {
+            None
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            writer: csv::Writer::from_writer(file),
+            header,
+            run: chrono::Local::now()
+                .format("%Y-%m-%dT%H:%M:%S%.3f%:z")
+                .to_string(),
+            count: 0,
+        })
+    }
+
+    fn append(&mut self, row: usize, values: &[(String, String)]) -> Result<(), String> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let error = |e: csv::Error| format!("Cannot write sidecar file \"{}\": {}", self.path, e);
+
+        let mut header = vec!["run".to_string(), "row".to_string()];
+        header.extend(values.iter().map(|(name, _)| name.clone()));
+        match &self.header {
+            Some(existing) if *existing != header => {
+                return Err(format!(
+                    "Sidecar file \"{}\" has other columns ({}), expected ({})",
+                    self.path,
+                    existing.join(","),
+                    header.join(",")
+                ));
+            }
+            Some(_) => {}
+            None => {
+                self.writer.write_record(&header).map_err(error)?;
+                self.header = Some(header);
+            }
+        }
+        let mut record = vec![self.run.clone(), row.to_string()];
+        record.extend(values.iter().map(|(_, value)| value.clone()));
+        self.writer.write_record(&record).map_err(error)?;
+        self.writer
+            .flush()
+            .map_err(|e| format!("Cannot write sidecar file \"{}\": {}", self.path, e))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn report(&self) {
+        if self.count > 0 {
+            eprintln!("Sidecar: {} rows saved to {:?}", self.count, self.path);
+        }
+    }
+}
+
+// Templates of row output, parsed once before rendering rows
+struct RowTemplates {
+    content: Option<Template>,
+    filename: Template,
+    base_image: Option<Template>,
+    // Templates of payload field values, by field value
+    payload_fields: HashMap<String, Template>,
+}
+
+impl RowTemplates {
+    fn parse(from_opt: &FromArg) -> Result<Self, String> {
+        let optional = |template: &Option<String>| template.as_deref().map(Template::parse);
+        Ok(Self {
+            content: optional(&from_opt.template_content).transpose()?,
+            filename: Template::parse(&from_opt.template_filename)?,
+            base_image: optional(&from_opt.common_arg.base_image).transpose()?,
+            payload_fields: from_opt
+                .common_arg
+                .payload_field
+                .iter()
+                .filter_map(|field| field.split_once('='))
+                .map(|(_, value)| Ok((value.to_string(), Template::parse(value)?)))
+                .collect::<Result<_, String>>()?,
+        })
+    }
+
+    // Payload of row when payload is set, see `build_payload`
+    fn payload(
+        &self,
+        common_arg: &CommonArg,
+        row: &[String],
+        headers: &[String],
+        position: &Position,
+    ) -> Option<Result<Payload, String>> {
+        build_payload(common_arg, |v| match self.payload_fields.get(v) {
+            Some(template) => template.render(row, headers, position, &common_arg.vars),
+            None => qrgen::utils::template::from_vec(
+                row.to_vec(),
+                headers,
+                v,
+                position,
+                &common_arg.vars,
+            ),
+        })
+    }
+}
+
+fn generate_list_console(
+    list_data: Vec<(usize, Vec<String>)>,
+    headers: &[String],
+    from_opt: &FromArg,
+    mut sidecar: Option<Sidecar>,
+) {
+    let templates = match RowTemplates::parse(from_opt) {
+        Ok(templates) => templates,
+        Err(e) => {
+            eprintln!("Error: {}", e);
             return;
         }
+    };
+    let vars = &from_opt.common_arg.vars;
+    let mut pack_versions = Vec::new();
+
+    let total = list_data.len();
+    for (counter, (index, row)) in list_data.into_iter().enumerate() {
+        let position = Position {
+            row: index,
+            counter,
+            total,
+        };
+        let payload = templates.payload(&from_opt.common_arg, &row, headers, &position);
+
+        let mut payload = match payload {
+            Some(Ok(payload)) => payload,
+            Some(Err(e)) => {
+                eprintln!("Error: row: {} > {}", index + 1, e);
+                continue;
+            }
+            // check content and error if empty
+            None => match &templates.content {
+                Some(template) => match template.render(&row, headers, &position, vars) {
+                    Ok(content) => Payload::from(content),
+                    Err(e) => {
+                        eprintln!("Error: row: {} > {}", index + 1, e);
+                        continue;
+                    }
+                },
+                // check content and error if empty
+                None => {
+                    eprintln!("Error: template_content is required for console output.");
+                    return;
+                }
+            },
+        };
+        match pack_payload(&from_opt.common_arg, &mut payload) {
+            Ok(versions) => pack_versions.extend(versions),
+            Err(e) => {
+                eprintln!("Error: row: {} > {}", index + 1, e);
+                continue;
+            }
+        }
+        if let Some(Err(e)) = sidecar
+            .as_mut()
+            .map(|s| s.append(index + 1, &payload.generated))
+        {
+            eprintln!("Error: row: {} > {}", index + 1, e);
+            continue;
+        }
+        if let Err(e) = qrgen::utils::console::print_payload(&payload) {
+            eprintln!("Error: row: {} > {}", index + 1, e);
+        }
+    }
 
-        let content = qrgen::utils::template::from_vec(
-            row.to_vec(),
-            &from_opt.template_content.clone().unwrap(),
-            index,
-        );
-        qrgen::utils::console::print_qr(&content)
+    report_pack(&pack_versions);
+    if let Some(sidecar) = &sidecar {
+        sidecar.report();
     }
 }
 
@@ -373,147 +1470,277 @@ fn get_font_db(fonts_path: Option<Vec<String>>) -> fontdb::Database {
     font_db
 }
 
-fn generate_list_image(list_data: Vec<Vec<String>>, from_opt: &FromArg, to_base64: bool) {
-    create_dir_all(from_opt.common_arg.outdir.to_string())
-        .expect("Cannot create output directory!");
+fn generate_list_image(
+    list_data: Vec<(usize, Vec<String>)>,
+    headers: &[String],
+    from_opt: &FromArg,
+    to_base64: bool,
+    sidecar: Option<Sidecar>,
+) {
+    let to_stdout = from_opt.common_arg.outdir == STDIO;
+    if !to_base64 && !to_stdout {
+        create_dir_all(from_opt.common_arg.outdir.to_string())
+            .expect("Cannot create output directory!");
+    }
 
     let font_db = get_font_db(from_opt.common_arg.font_path.clone());
+    let vars = &from_opt.common_arg.vars;
+
+    let template_draw: Option<ValueTemplate> = match &from_opt.common_arg.template_draw_string {
+        Some(t) => match json5::from_str(t) {
+            Ok(v) => match ValueTemplate::parse(&v) {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return;
+                }
+            },
+            Err(e) => {
+                eprintln!("Error: Invalid template draw format: {}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+    let templates = match RowTemplates::parse(from_opt) {
+        Ok(templates) => templates,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
 
     // Generate file name list
     let mut file_name_count_map: HashMap<String, u32> = HashMap::new();
-    let list_data_file_name: Vec<String> = list_data
+    let list_data_file_name: Vec<Result<String, String>> = list_data
         .iter()
         .enumerate()
-        .map(|(index, row)| {
-            let raw_filename =
-                qrgen::utils::template::from_vec(row.to_vec(), &from_opt.template_filename, index);
+        .map(|(counter, (index, row))| {
+            let position = Position {
+                row: *index,
+                counter,
+                total: list_data.len(),
+            };
+            let raw_filename = templates.filename.render(row, headers, &position, vars)?;
             let mut filename = raw_filename.replace("/", "_");
             let number_dup = file_name_count_map.get(&filename).unwrap_or(&0).clone();
             file_name_count_map.insert(filename.clone(), number_dup + 1);
             if number_dup > 0 {
                 filename = format!("{}_{}", filename, number_dup + 1);
             }
-            format!("{}/{}.png", &from_opt.common_arg.outdir, filename)
+            if to_stdout {
+                return Ok(STDIO.to_string());
+            }
+            Ok(format!("{}/{}.png", &from_opt.common_arg.outdir, filename))
         })
         .collect();
 
     // Generate QR images
-    let result_generate_image: Vec<bool> = list_data
-        .par_iter()
-        .enumerate()
-        .map(|(index, row)| -> bool {
-            // check content and error if empty
-
-            let content = if from_opt.template_content.is_some() {
-                Some(qrgen::utils::template::from_vec(
-                    row.to_vec(),
-                    &from_opt.template_content.clone().unwrap(),
-                    index,
-                ))
-            } else {
-                None
-            };
-
-            let template_draw_string = match &from_opt.common_arg.template_draw_string {
-                Some(t) => Some(qrgen::utils::template::from_vec(row.to_vec(), &t, index)),
-                None => None,
-            };
+    let sidecar = sidecar.map(Mutex::new);
+    let pack_versions = Mutex::new(Vec::new());
+    let generate_row = |(counter, (index, row)): (usize, &(usize, Vec<String>))| -> bool {
+        let index = *index;
+        let position = Position {
+            row: index,
+            counter,
+            total: list_data.len(),
+        };
+        let path = match &list_data_file_name[counter] {
+            Ok(path) => path.clone(),
+            Err(e) => {
+                eprintln!("Error: row: {} > {}", index + 1, e);
+                return false;
+            }
+        };
 
-            let template_draw = match template_draw_string {
-                Some(t) => Some(json5::from_str(&t).expect("Invalid template draw format")),
-                None => None,
-            };
+        // check content and error if empty
+        let payload = templates.payload(&from_opt.common_arg, row, headers, &position);
 
-            let base_image = match &from_opt.common_arg.base_image {
-                Some(v) => Some(qrgen::utils::template::from_vec(row.to_vec(), v, index)),
+        let mut payload = match payload {
+            Some(Ok(payload)) => Some(payload),
+            Some(Err(e)) => {
+                eprintln!("Error: row: {} > {}", index + 1, e);
+                return false;
+            }
+            None => match &templates.content {
+                Some(template) => match template.render(row, headers, &position, vars) {
+                    Ok(content) => Some(Payload::from(content)),
+                    Err(e) => {
+                        eprintln!("Error: row: {} > {}", index + 1, e);
+                        return false;
+                    }
+                },
                 None => None,
-            };
-
-            let gen_image_opt = qrgen::utils::generate::GenerateImageOptions {
-                qr_color: (
-                    from_opt.common_arg.qr_color_0.clone(),
-                    from_opt.common_arg.qr_color_1.clone(),
-                ),
-                base_image: base_image,
-                fill_color: from_opt.common_arg.fill_color.clone(),
-                image_width: from_opt.common_arg.image_width,
-                image_height: from_opt
-                    .common_arg
-                    .image_height
-                    .unwrap_or(from_opt.common_arg.image_width),
-                qr_size: from_opt
-                    .common_arg
-                    .qr_size
-                    .unwrap_or(from_opt.common_arg.image_width),
-                pos_qr_x: from_opt.common_arg.pos_qr_x,
-                pos_qr_y: from_opt.common_arg.pos_qr_y,
-                error_correction_level: from_opt.common_arg.error_correction_level.clone(),
-                template_draw: template_draw,
-                font_size: from_opt.common_arg.font_size,
-                reduce_font_size: from_opt.common_arg.reduce_font_size,
-                font_db: font_db.clone(),
-            };
+            },
+        };
+        if let Some(payload) = payload.as_mut() {
+            match pack_payload(&from_opt.common_arg, payload) {
+                Ok(versions) => pack_versions.lock().unwrap().extend(versions),
+                Err(e) => {
+                    eprintln!("Error: row: {} > {}", index + 1, e);
+                    return false;
+                }
+            }
+        }
+        let content = payload.as_ref().map(|p| p.content.clone());
 
-            let generate_image_result =
-                qrgen::utils::generate::generate_image(content, gen_image_opt);
+        if let (Some(sidecar), Some(p)) = (&sidecar, payload.as_ref()) {
+            if let Err(e) = sidecar.lock().unwrap().append(index + 1, &p.generated) {
+                eprintln!("Error: row: {} > {}", index + 1, e);
+                return false;
+            }
+        }
 
-            handler_result_generate_image(
-                index + 1,
-                &generate_image_result,
-                list_data_file_name.get(index).unwrap().to_string(),
-                to_base64,
-            )
-        })
-        .collect();
+        // Data is put in string values of parsed template, so it cannot break format
+        let template_draw = match &template_draw {
+            Some(t) => {
+                let t = match t.render(row, headers, &position, vars) {
+                    Ok(t) => t,
+                    Err(e) => {
+                        eprintln!("Error: row: {} > {}", index + 1, e);
+                        return false;
+                    }
+                };
+                match from_value::<Vec<DrawItem>>(t) {
+                    Ok(t) => Some(t),
+                    Err(e) => {
+                        eprintln!(
+                            "Error: row: {} > Invalid template draw format: {}",
+                            index + 1,
+                            e
+                        );
+                        return false;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let base_image = match &templates.base_image {
+            Some(t) => match t.render(row, headers, &position, vars) {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    eprintln!("Error: row: {} > {}", index + 1, e);
+                    return false;
+                }
+            },
+            None => None,
+        };
+
+        let gen_image_opt = qrgen::utils::generate::GenerateImageOptions {
+            qr_color: (
+                from_opt.common_arg.qr_color_0.clone(),
+                from_opt.common_arg.qr_color_1.clone(),
+            ),
+            base_image: base_image,
+            fill_color: from_opt.common_arg.fill_color.clone(),
+            image_width: from_opt.common_arg.image_width,
+            image_height: from_opt
+                .common_arg
+                .image_height
+                .unwrap_or(from_opt.common_arg.image_width),
+            qr_size: from_opt
+                .common_arg
+                .qr_size
+                .unwrap_or(from_opt.common_arg.image_width),
+            pos_qr_x: from_opt.common_arg.pos_qr_x,
+            pos_qr_y: from_opt.common_arg.pos_qr_y,
+            error_correction_level: payload
+                .as_ref()
+                .and_then(|p| p.error_correction_level.clone())
+                .unwrap_or(from_opt.common_arg.error_correction_level.clone()),
+            template_draw: template_draw,
+            font_size: from_opt.common_arg.font_size,
+            reduce_font_size: from_opt.common_arg.reduce_font_size,
+            font_db: font_db.clone(),
+            swiss_cross: payload.as_ref().is_some_and(|p| p.swiss_cross),
+            gs1: payload.as_ref().is_some_and(|p| p.gs1),
+            datamatrix: payload.as_ref().is_some_and(|p| p.datamatrix),
+        };
+
+        let generate_image_result = qrgen::utils::generate::generate_image(content, gen_image_opt);
+
+        handler_result_generate_image(index + 1, &generate_image_result, path, to_base64)
+    };
+    // Stream to stdout in row order, files are generated in parallel
+    let result_generate_image: Vec<bool> = if to_stdout {
+        list_data.iter().enumerate().map(generate_row).collect()
+    } else {
+        list_data.par_iter().enumerate().map(generate_row).collect()
+    };
 
     let count_success = result_generate_image.iter().filter(|x| **x).count();
     let count_error = result_generate_image.iter().count() - count_success;
 
-    println!("Success: {}, Error: {} files.", count_success, count_error);
+    report_pack(&pack_versions.into_inner().unwrap());
+    eprintln!("Success: {}, Error: {} files.", count_success, count_error);
+
+    if let Some(sidecar) = sidecar {
+        sidecar.into_inner().unwrap().report();
+    }
 }
 
 fn handler_result_generate_image(
     row_number: usize,
-    result: &Result<qrgen::utils::generate::ResultGenerateImage, String>,
+    result: &Result<
+        qrgen::utils::generate::ResultGenerateImage,
+        qrgen::utils::generate::GenerateError,
+    >,
     path: String,
     to_base64: bool,
 ) -> bool {
     match result {
         Err(e) => {
-            println!("Error: row: {} > {:#?}", row_number, e);
+            eprintln!("Error: row: {} > {}", row_number, e);
             false
         }
         Ok(r) => {
             // Info when font size was reduced
             if r.reduce_font_size {
-                println!("Info: Font size reduced for: {}", path);
+                eprintln!("Info: Font size reduced for: {}", path);
             }
 
             // Info when some draw out pixel
             if r.draw_out_pixel {
-                println!("Info: Some pixels drawn out of bounds: {}", path);
+                eprintln!("Info: Some pixels drawn out of bounds: {}", path);
             }
 
-            if to_base64 {
+            if to_base64 || path == STDIO {
                 let mut bytes: Vec<u8> = Vec::new();
                 r.image_buffer
                     .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
                     .expect("Couldn't write image to bytes.");
 
+                if !to_base64 {
+                    // Stream PNG bytes to stdout
+                    let mut stdout = std::io::stdout().lock();
+                    return match stdout.write_all(&bytes).and_then(|_| stdout.flush()) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            eprintln!("Error: row: {} > {}", row_number, e);
+                            false
+                        }
+                    };
+                }
+
                 let b64 = general_purpose::STANDARD.encode(bytes);
 
-                println!("result_base64:{}:{}", path, &b64);
+                if path == STDIO {
+                    println!("{}", &b64);
+                } else {
+                    println!("result_base64:{}:{}", path, &b64);
+                }
                 true
             } else {
                 let save_image = r.image_buffer.save(&path);
 
                 match save_image {
                     Ok(_) => {
-                        println!("Created: {:?}", &path);
+                        eprintln!("Created: {:?}", &path);
                         true
                     }
                     Err(e) => {
-                        println!("Error: {} > {}", e, path);
+                        eprintln!("Error: {} > {}", e, path);
                         false
                     }
                 }
@@ -1,5 +1,8 @@
 use qrcode_generator::QrCodeEcc;
 
+use super::generate::GenerateError;
+use super::payload::Payload;
+
 // Print the given qrcode object to the console
 fn from_vec_bool(qr: &Vec<Vec<bool>>) {
     let symbol: char = '█';
@@ -31,9 +34,34 @@ fn from_vec_bool(qr: &Vec<Vec<bool>>) {
     println!();
 }
 
-pub fn print_qr(content: &String) {
+pub fn print_qr(content: &String) -> Result<(), GenerateError> {
+    let result: Vec<Vec<bool>> =
+        qrcode_generator::to_matrix(content, QrCodeEcc::Low).map_err(|_| {
+            GenerateError::ContentTooLong {
+                length: content.len(),
+            }
+        })?;
     println!("{}", content);
-    let result: Vec<Vec<bool>> = qrcode_generator::to_matrix(content, QrCodeEcc::Low).unwrap();
     from_vec_bool(&result);
     println!();
+    Ok(())
+}
+
+/// Print payload content as QR, or in the symbol required by payload (GS1 QR, GS1 DataMatrix)
+pub fn print_payload(payload: &Payload) -> Result<(), GenerateError> {
+    if !payload.gs1 {
+        return print_qr(&payload.content);
+    }
+    let result = if payload.datamatrix {
+        super::datamatrix::to_matrix_gs1(&payload.content)
+    } else {
+        super::qr::to_matrix_gs1(&payload.content, QrCodeEcc::Low)
+    }
+    .map_err(|_| GenerateError::ContentTooLong {
+        length: payload.content.len(),
+    })?;
+    println!("{}", payload.content);
+    from_vec_bool(&result);
+    println!();
+    Ok(())
 }
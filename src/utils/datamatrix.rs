@@ -0,0 +1,83 @@
+use datamatrix::{
+    errorcode, placement::MatrixMap, DataMatrixBuilder, EncodationType, SymbolList, SymbolSize,
+};
+
+// ASCII encodation codewords (ISO/IEC 16022)
+const FNC1: u8 = 232;
+const PAD: u8 = 129;
+const UPPER_SHIFT: u8 = 235;
+// ASCII value + 1
+const GS_CODEWORD: u8 = 0x1d + 1;
+
+/// Data codewords of GS1 DataMatrix (ASCII encodation, FNC1 in first position),
+/// GS (0x1D) in content is encoded as FNC1 separator
+pub fn gs1_codewords(content: &str) -> Result<(SymbolSize, Vec<u8>), String> {
+    let symbol = DataMatrixBuilder::new()
+        .with_encodation_types(EncodationType::Ascii)
+        .with_macros(false)
+        .with_fnc1_start(true)
+        // Square symbols only, image is drawn as a square
+        .with_symbol_list(SymbolList::default().enforce_square())
+        .encode(content.as_bytes())
+        .map_err(|_| "Content is too long for Data Matrix".to_string())?;
+
+    // GS and FNC1 are both one codeword in ASCII encodation, so symbol size is the same
+    let mut codewords = symbol.data_codewords().to_vec();
+    let mut index = 1;
+    while index < codewords.len() {
+        match codewords[index] {
+            PAD => break,
+            UPPER_SHIFT => index += 1,
+            GS_CODEWORD => codewords[index] = FNC1,
+            _ => {}
+        }
+        index += 1;
+    }
+    Ok((symbol.size, codewords))
+}
+
+/// Encode GS1 DataMatrix, GS (0x1D) in content is FNC1 separator.
+/// Return modules as rows (`matrix[y][x]`), same as `qrcode_generator::to_matrix`
+pub fn to_matrix_gs1(content: &str) -> Result<Vec<Vec<bool>>, String> {
+    let (size, data) = gs1_codewords(content)?;
+
+    let mut codewords = data.clone();
+    codewords.extend(errorcode::encode_error(&data, size));
+    let bitmap = MatrixMap::new_with_codewords(&codewords, size).bitmap();
+    Ok(bitmap
+        .bits()
+        .chunks(bitmap.width())
+        .map(|row| row.to_vec())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use datamatrix::DataMatrix;
+
+    const ELEMENT_STRING: &str = "010950110153000310AB-123\x1d21SERIAL42";
+
+    #[test]
+    fn gs1_fnc1_codewords() {
+        let (_, codewords) = gs1_codewords(ELEMENT_STRING).unwrap();
+        assert_eq!(codewords[0], FNC1);
+        // `01` and `09` as digit pairs (130 + value)
+        assert_eq!(codewords[1..3], [131, 139]);
+        assert_eq!(codewords.iter().filter(|c| **c == FNC1).count(), 2);
+        assert!(!codewords.contains(&GS_CODEWORD));
+    }
+
+    #[test]
+    fn gs1_round_trip() {
+        let matrix = to_matrix_gs1(ELEMENT_STRING).unwrap();
+        let width = matrix[0].len();
+        assert_eq!(matrix.len(), width);
+        let pixels: Vec<bool> = matrix.concat();
+        // Decoder drops leading FNC1 and returns separator FNC1 as GS
+        assert_eq!(
+            DataMatrix::decode(&pixels, width).unwrap(),
+            ELEMENT_STRING.as_bytes()
+        );
+    }
+}
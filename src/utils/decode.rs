@@ -0,0 +1,41 @@
+use rust_text_draw::image::{load_from_memory, open, DynamicImage};
+use std::io::Read;
+
+/// Read contents of QR codes in image file, path `-` reads image from stdin
+pub fn decode_image(path: &str) -> Result<Vec<String>, String> {
+    let image = if path == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Cannot read stdin: {}", e))?;
+        load_from_memory(&bytes)
+    } else {
+        open(path)
+    }
+    .map_err(|e| format!("Cannot open image \"{}\": {}", path, e))?;
+
+    decode(&image)
+}
+
+/// Read contents of QR codes in image
+pub fn decode(image: &DynamicImage) -> Result<Vec<String>, String> {
+    let luma = image.to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        luma.width() as usize,
+        luma.height() as usize,
+        |x, y| luma.get_pixel(x as u32, y as u32)[0],
+    );
+
+    let grids = prepared.detect_grids();
+    if grids.is_empty() {
+        return Err("No QR code found".to_string());
+    }
+    grids
+        .iter()
+        .map(|grid| {
+            grid.decode()
+                .map(|(_, content)| content)
+                .map_err(|e| format!("Cannot decode QR code: {}", e))
+        })
+        .collect()
+}
@@ -1,7 +1,88 @@
-use qrcode_generator::QrCodeEcc;
 use rust_text_draw::image::{open, DynamicImage, Rgba};
 use rust_text_draw::{draw_text, GenericImageView, Widget};
 use rust_text_draw::{fontdb, FontSystem, GenericImage, SwashCache};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::fmt;
+
+use super::datamatrix;
+use super::qr::{parse_ecc, to_matrix_gs1};
+use super::shape::{draw_shape, parse_color, Shape};
+
+/// Error of generating image, the image is skipped and the next one can be generated
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenerateError {
+    /// Color is not hex `rrggbb` or `rrggbbaa`
+    InvalidColor { name: &'static str, value: String },
+    /// Base image cannot be opened
+    BaseImage { path: String, message: String },
+    /// Content does not fit in QR code (version 40)
+    ContentTooLong { length: usize },
+    /// QR size (pixels) is smaller than its modules with margin
+    QrTooSmall { size: u32, modules: u32 },
+    /// QR at position does not fit in image
+    QrOutOfBounds {
+        x: u32,
+        y: u32,
+        size: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Drawing text or shape of draw template failed
+    Draw(String),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::InvalidColor { name, value } => {
+                write!(
+                    f,
+                    "Invalid {}: \"{}\" (hex rrggbb or rrggbbaa)",
+                    name, value
+                )
+            }
+            GenerateError::BaseImage { path, message } => {
+                write!(f, "Cannot open base image \"{}\": {}", path, message)
+            }
+            GenerateError::ContentTooLong { length } => write!(
+                f,
+                "Content is too long for QR code ({} bytes), try --pack or lower --ecc",
+                length
+            ),
+            GenerateError::QrTooSmall { size, modules } => write!(
+                f,
+                "QR size {} is too small for {} modules (at least {} pixels)",
+                size,
+                modules,
+                modules + 2
+            ),
+            GenerateError::QrOutOfBounds {
+                x,
+                y,
+                size,
+                width,
+                height,
+            } => write!(
+                f,
+                "QR at ({}, {}) with size {} is out of image {} x {}",
+                x, y, size, width, height
+            ),
+            GenerateError::Draw(e) => write!(f, "Unsuccess: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+// Parse hex color of option
+fn color(name: &'static str, value: &str) -> Result<[u8; 4], GenerateError> {
+    parse_color(value).map_err(|_| GenerateError::InvalidColor {
+        name,
+        value: value.to_string(),
+    })
+}
 
 pub struct ResultGenerateImage {
     pub image_buffer: DynamicImage,
@@ -9,6 +90,28 @@ pub struct ResultGenerateImage {
     pub draw_out_pixel: bool,
 }
 
+/// Item of draw template, a text widget or a vector shape (has `shape` key)
+#[derive(Debug)]
+pub enum DrawItem {
+    Widget(Widget),
+    Shape(Shape),
+}
+
+impl<'de> Deserialize<'de> for DrawItem {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        if value.get("shape").is_some() {
+            Shape::deserialize(value)
+                .map(DrawItem::Shape)
+                .map_err(D::Error::custom)
+        } else {
+            Widget::deserialize(value)
+                .map(DrawItem::Widget)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
 pub struct GenerateImageOptions {
     pub qr_color: (String, String),
     pub base_image: Option<String>,
@@ -19,10 +122,16 @@ pub struct GenerateImageOptions {
     pub error_correction_level: String,
     pub pos_qr_x: u32,
     pub pos_qr_y: u32,
-    pub template_draw: Option<Vec<Widget>>,
+    pub template_draw: Option<Vec<DrawItem>>,
     pub font_size: f32,
     pub reduce_font_size: u32,
     pub font_db: fontdb::Database,
+    /// Draw Swiss cross on center of QR (Swiss QR-bill)
+    pub swiss_cross: bool,
+    /// Encode QR in GS1 mode (FNC1 in first position)
+    pub gs1: bool,
+    /// Encode GS1 DataMatrix instead of QR (with `gs1`)
+    pub datamatrix: bool,
 }
 
 pub fn get_alpha_color(base_color: Rgba<u8>, color: [u8; 4]) -> [u8; 4] {
@@ -39,20 +148,50 @@ pub fn get_alpha_color(base_color: Rgba<u8>, color: [u8; 4]) -> [u8; 4] {
     return [r, g, b, alpha];
 }
 
+/// Draw Swiss cross (7 x 7 mm on 46 x 46 mm symbol) at center point
+pub fn draw_swiss_cross(
+    image: &mut DynamicImage,
+    center_x: u32,
+    center_y: u32,
+    symbol_size: u32,
+    color: ([u8; 4], [u8; 4]),
+) {
+    let unit = symbol_size as f32 / 46.0;
+    let mut fill_square = |width: f32, height: f32, color: [u8; 4]| {
+        let x0 = (center_x as f32 - width / 2.0).round() as u32;
+        let y0 = (center_y as f32 - height / 2.0).round() as u32;
+        for y in y0..y0 + height.round() as u32 {
+            for x in x0..x0 + width.round() as u32 {
+                if x < image.width() && y < image.height() {
+                    let base = image.get_pixel(x, y);
+                    image.put_pixel(x, y, Rgba(get_alpha_color(base, color)));
+                }
+            }
+        }
+    };
+
+    // White border, black square and white cross
+    fill_square(7.0 * unit, 7.0 * unit, color.0);
+    fill_square(6.0 * unit, 6.0 * unit, color.1);
+    fill_square(1.17 * unit, 3.89 * unit, color.0);
+    fill_square(3.89 * unit, 1.17 * unit, color.0);
+}
+
 pub fn generate_image(
     content: Option<String>,
     opt: GenerateImageOptions,
-) -> Result<ResultGenerateImage, String> {
+) -> Result<ResultGenerateImage, GenerateError> {
     // Create a new image with additional space at the top
-    let mut new_image = if opt.base_image.is_some() {
-        open(opt.base_image.unwrap()).unwrap()
+    let mut new_image = if let Some(path) = &opt.base_image {
+        open(path).map_err(|e| GenerateError::BaseImage {
+            path: path.clone(),
+            message: e.to_string(),
+        })?
     } else {
         let mut temp_new_image = DynamicImage::new_rgba8(opt.image_width, opt.image_height);
 
         // Set widget color
-        let fill_color_rgba: [u8; 4] = u32::from_str_radix(opt.fill_color.as_str(), 16)
-            .unwrap()
-            .to_be_bytes();
+        let fill_color_rgba = color("fill color", &opt.fill_color)?;
 
         // fill bg base image
         for y in 0..temp_new_image.height() as u32 {
@@ -66,32 +205,58 @@ pub fn generate_image(
     // Generate and draw QR
     if opt.qr_size != 0 && content.is_some() {
         // set qr error correction level
-        let ecc = match opt.error_correction_level.as_str() {
-            "l" => QrCodeEcc::Low,
-            "m" => QrCodeEcc::Medium,
-            "q" => QrCodeEcc::Quartile,
-            "h" => QrCodeEcc::High,
-            _ => QrCodeEcc::Medium,
-        };
+        let ecc = parse_ecc(opt.error_correction_level.as_str());
 
-        // Generate a QR code and convert it to ImageBuffer
-        let qr_code_buffer = qrcode_generator::to_image_buffer(content.unwrap(), ecc, opt.qr_size as usize)
-            .expect("Failed to generate QR code");
+        let content = content.unwrap();
 
-        // Copy the QR code image onto the new image
-        let qr_color_0: [u8; 4] = u32::from_str_radix(opt.qr_color.0.as_str(), 16)
-            .unwrap()
-            .to_be_bytes();
+        // Generate QR modules
+        let matrix = if opt.gs1 && opt.datamatrix {
+            datamatrix::to_matrix_gs1(&content).ok()
+        } else if opt.gs1 {
+            to_matrix_gs1(&content, ecc).ok()
+        } else {
+            qrcode_generator::to_matrix(&content, ecc).ok()
+        }
+        .ok_or(GenerateError::ContentTooLong {
+            length: content.len(),
+        })?;
 
-        let qr_color_1: [u8; 4] = u32::from_str_radix(opt.qr_color.1.as_str(), 16)
-            .unwrap()
-            .to_be_bytes();
+        // Module size and margin (1 module at least), same as `qrcode_generator::to_image`
+        let modules = matrix.len() as u32;
+        let point_size = opt.qr_size / (modules + 2);
+        if point_size == 0 {
+            return Err(GenerateError::QrTooSmall {
+                size: opt.qr_size,
+                modules,
+            });
+        }
+        let fits = |position: u32, size: u32| position as u64 + opt.qr_size as u64 <= size as u64;
+        if !fits(opt.pos_qr_x, new_image.width()) || !fits(opt.pos_qr_y, new_image.height()) {
+            return Err(GenerateError::QrOutOfBounds {
+                x: opt.pos_qr_x,
+                y: opt.pos_qr_y,
+                size: opt.qr_size,
+                width: new_image.width(),
+                height: new_image.height(),
+            });
+        }
+        let margin = (opt.qr_size - point_size * modules) / 2;
+        let is_dark = |x: u32, y: u32| {
+            let range = margin..margin + point_size * modules;
+            range.contains(&x)
+                && range.contains(&y)
+                && matrix[((y - margin) / point_size) as usize]
+                    [((x - margin) / point_size) as usize]
+        };
 
-        for x in 0..qr_code_buffer.width() {
-            for y in 0..qr_code_buffer.height() {
-                let pixel_value = qr_code_buffer.get_pixel(x, y);
+        // Copy the QR code image onto the new image
+        let qr_color_0 = color("QR color 0", &opt.qr_color.0)?;
+        let qr_color_1 = color("QR color 1", &opt.qr_color.1)?;
+
+        for x in 0..opt.qr_size {
+            for y in 0..opt.qr_size {
                 let new_image_pixel = new_image.get_pixel(opt.pos_qr_x + x, opt.pos_qr_y + y);
-                if pixel_value[0] == 0 {
+                if is_dark(x, y) {
                     let color = get_alpha_color(new_image_pixel, qr_color_1);
                     new_image.put_pixel(opt.pos_qr_x + x, opt.pos_qr_y + y, Rgba(color));
                 } else {
@@ -100,6 +265,16 @@ pub fn generate_image(
                 }
             }
         }
+
+        if opt.swiss_cross {
+            draw_swiss_cross(
+                &mut new_image,
+                opt.pos_qr_x + opt.qr_size / 2,
+                opt.pos_qr_y + opt.qr_size / 2,
+                point_size * modules,
+                (qr_color_0, qr_color_1),
+            );
+        }
     }
 
     if opt.template_draw.is_none() {
@@ -110,10 +285,10 @@ pub fn generate_image(
         });
     }
 
-    // Process draw text to image
+    // Process draw text and shapes to image
 
-    let widgets = opt.template_draw.unwrap();
-    // println!("deserialized = {:#?}", widgets);
+    let items = opt.template_draw.unwrap();
+    // println!("deserialized = {:#?}", items);
 
     let mut swash_cache = SwashCache::new();
     let text_layout_width = new_image.width();
@@ -124,26 +299,47 @@ pub fn generate_image(
     // for wait to set and return result
     let reduce_font_size = false;
 
-    let result_draw_text = draw_text(
-        &mut swash_cache,
-        &mut font_system,
-        &mut new_image,
-        0.0,
-        0.0,
-        text_layout_width as f32,
-        text_layout_height as f32,
-        widgets,
-        opt.font_size as f32,
-        &"000000".to_string(),
-        true,
-    );
-
-    match result_draw_text {
-        Ok(r) => Ok(ResultGenerateImage {
-            image_buffer: new_image,
-            reduce_font_size,
-            draw_out_pixel: r.count_pixel_out > 0,
-        }),
-        Err(e) => Err(format!("Unsuccess: {}", e)),
+    let mut count_pixel_out = 0;
+
+    // Draw in template order, consecutive widgets are drawn together
+    let mut widgets: Vec<Widget> = Vec::new();
+    let mut items = items.into_iter().peekable();
+    while let Some(item) = items.next() {
+        match item {
+            DrawItem::Shape(shape) => {
+                count_pixel_out +=
+                    draw_shape(&mut new_image, &shape).map_err(GenerateError::Draw)?;
+            }
+            DrawItem::Widget(widget) => widgets.push(widget),
+        }
+
+        if widgets.is_empty() || matches!(items.peek(), Some(DrawItem::Widget(_))) {
+            continue;
+        }
+
+        let result_draw_text = draw_text(
+            &mut swash_cache,
+            &mut font_system,
+            &mut new_image,
+            0.0,
+            0.0,
+            text_layout_width as f32,
+            text_layout_height as f32,
+            std::mem::take(&mut widgets),
+            opt.font_size,
+            &"000000".to_string(),
+            true,
+        );
+
+        match result_draw_text {
+            Ok(r) => count_pixel_out += r.count_pixel_out,
+            Err(e) => return Err(GenerateError::Draw(e.to_string())),
+        }
     }
+
+    Ok(ResultGenerateImage {
+        image_buffer: new_image,
+        reduce_font_size,
+        draw_out_pixel: count_pixel_out > 0,
+    })
 }
@@ -1,4 +1,40 @@
 pub mod console;
+pub mod datamatrix;
+pub mod decode;
 pub mod generate;
+pub mod pack;
+pub mod payload;
 pub mod process_file;
+pub mod qr;
+pub mod select;
+pub mod shape;
+pub mod sign;
 pub mod template;
+pub mod validate;
+
+/// Temporary directory for tests, removed with its files when dropped
+#[cfg(test)]
+pub(crate) struct TempDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("qrgen-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// Write file in directory, return its path
+    pub(crate) fn file(&self, name: &str, bytes: &[u8]) -> String {
+        let path = self.0.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().to_string()
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
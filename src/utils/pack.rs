@@ -0,0 +1,161 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, ZlibEncoder};
+use flate2::Compression;
+use std::io::{Read, Write};
+
+// Base45 alphabet (RFC 9285), same characters as QR alphanumeric mode
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Base45 encode (RFC 9285), as used by EU Digital COVID Certificate
+pub fn base45_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len() / 2 * 3 + 2);
+    for chunk in data.chunks(2) {
+        let (mut value, count) = match chunk {
+            [a, b] => (*a as usize * 256 + *b as usize, 3),
+            [a] => (*a as usize, 2),
+            _ => unreachable!(),
+        };
+        for _ in 0..count {
+            output.push(BASE45_ALPHABET[value % 45] as char);
+            value /= 45;
+        }
+    }
+    output
+}
+
+/// Base45 decode (RFC 9285)
+pub fn base45_decode(text: &str) -> Result<Vec<u8>, String> {
+    let values = text
+        .bytes()
+        .map(|c| {
+            BASE45_ALPHABET
+                .iter()
+                .position(|a| *a == c)
+                .ok_or(format!("Invalid base45 character: \"{}\"", c as char))
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    let mut data = Vec::with_capacity(values.len() / 3 * 2 + 1);
+    for chunk in values.chunks(3) {
+        let value = chunk.iter().rev().fold(0, |value, v| value * 45 + v);
+        match chunk.len() {
+            3 if value <= 0xffff => data.extend([(value >> 8) as u8, value as u8]),
+            2 if value <= 0xff => data.push(value as u8),
+            _ => return Err("Invalid base45 data".to_string()),
+        }
+    }
+    Ok(data)
+}
+
+// Split method `compression+encoding` (compression is optional)
+fn parse_method(method: &str) -> Result<(Option<&str>, &str), String> {
+    let (compression, encoding) = match method.split_once('+') {
+        Some((compression, encoding)) => (Some(compression), encoding),
+        None => (None, method),
+    };
+    let valid_compression = compression.is_none_or(|c| matches!(c, "deflate" | "zlib" | "zstd"));
+    if !valid_compression || !matches!(encoding, "base45" | "base64url") {
+        return Err(format!(
+            "Invalid pack method: \"{}\" ([deflate|zlib|zstd+]base45|base64url)",
+            method
+        ));
+    }
+    Ok((compression, encoding))
+}
+
+/// Compress content and encode it to text, method is `compression+encoding`,
+/// e.g. `zlib+base45` (EU DCC), `zstd+base64url` or `base45` (no compression)
+pub fn pack(content: &str, method: &str) -> Result<String, String> {
+    let (compression, encoding) = parse_method(method)?;
+
+    let data = content.as_bytes();
+    let data = match compression {
+        None => data.to_vec(),
+        Some("zstd") => {
+            ruzstd::encoding::compress_to_vec(data, ruzstd::encoding::CompressionLevel::Fastest)
+        }
+        Some("deflate") => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(data).map_err(|e| e.to_string())?;
+            encoder.finish().map_err(|e| e.to_string())?
+        }
+        Some(_) => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(data).map_err(|e| e.to_string())?;
+            encoder.finish().map_err(|e| e.to_string())?
+        }
+    };
+
+    Ok(match encoding {
+        "base45" => base45_encode(&data),
+        _ => URL_SAFE_NO_PAD.encode(data),
+    })
+}
+
+/// Decode and decompress content packed by `pack` with the same method
+pub fn unpack(packed: &str, method: &str) -> Result<String, String> {
+    let (compression, encoding) = parse_method(method)?;
+
+    let data = match encoding {
+        "base45" => base45_decode(packed)?,
+        _ => URL_SAFE_NO_PAD
+            .decode(packed.trim_end_matches('='))
+            .map_err(|_| "Content is not base64url".to_string())?,
+    };
+
+    let mut content = Vec::new();
+    let result = match compression {
+        None => {
+            content = data;
+            Ok(0)
+        }
+        Some("zstd") => ruzstd::decoding::StreamingDecoder::new(data.as_slice())
+            .map_err(|e| std::io::Error::other(e.to_string()))
+            .and_then(|mut decoder| decoder.read_to_end(&mut content)),
+        Some("deflate") => DeflateDecoder::new(data.as_slice()).read_to_end(&mut content),
+        Some(_) => ZlibDecoder::new(data.as_slice()).read_to_end(&mut content),
+    };
+    result.map_err(|e| format!("Cannot decompress content: {}", e))?;
+
+    String::from_utf8(content).map_err(|_| "Unpacked content is not UTF-8 text".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 9285 examples
+    const BASE45: [(&str, &str); 4] = [
+        ("AB", "BB8"),
+        ("Hello!!", "%69 VD92EX0"),
+        ("base-45", "UJCLQE7W581"),
+        ("ietf!", "QED8WEX0"),
+    ];
+
+    #[test]
+    fn base45_rfc9285() {
+        for (data, encoded) in BASE45 {
+            assert_eq!(base45_encode(data.as_bytes()), encoded);
+            assert_eq!(base45_decode(encoded).unwrap(), data.as_bytes());
+        }
+        assert!(base45_decode("GGW").is_err());
+        assert!(base45_decode("ab").is_err());
+    }
+
+    #[test]
+    fn pack_round_trip() {
+        let content = "{\"id\": 1234, \"name\": \"Ticket\", \"seat\": \"A-12\"}".repeat(4);
+        for method in [
+            "base45",
+            "base64url",
+            "deflate+base45",
+            "zlib+base45",
+            "zstd+base64url",
+        ] {
+            let packed = pack(&content, method).unwrap();
+            assert_eq!(unpack(&packed, method).unwrap(), content, "{}", method);
+        }
+        assert!(pack(&content, "gzip+base45").is_err());
+    }
+}
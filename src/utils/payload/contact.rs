@@ -0,0 +1,324 @@
+use super::{optional, required, Fields};
+
+/// Contact card, output as vCard or MeCard
+#[derive(Debug, Clone, Default)]
+pub struct Contact {
+    /// Formatted name
+    pub name: String,
+    pub family_name: Option<String>,
+    pub given_name: Option<String>,
+    pub org: Option<String>,
+    pub title: Option<String>,
+    pub phones: Vec<String>,
+    pub emails: Vec<String>,
+    pub url: Option<String>,
+    pub address: Address,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Address {
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub postcode: Option<String>,
+    pub country: Option<String>,
+}
+
+impl Address {
+    fn is_empty(&self) -> bool {
+        self.parts().iter().all(|p| p.is_none())
+    }
+
+    fn parts(&self) -> [&Option<String>; 5] {
+        [
+            &self.street,
+            &self.city,
+            &self.region,
+            &self.postcode,
+            &self.country,
+        ]
+    }
+}
+
+/// vCard version
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VCardVersion {
+    V3,
+    V4,
+}
+
+impl VCardVersion {
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        match optional(fields, "version") {
+            None | Some("3") | Some("3.0") => Ok(VCardVersion::V3),
+            Some("4") | Some("4.0") => Ok(VCardVersion::V4),
+            Some(v) => Err(format!("Unsupported vCard version: \"{}\"", v)),
+        }
+    }
+}
+
+impl Contact {
+    /// Fields: `name`, `family`, `given`, `org`, `title`, `phone`, `email` (multiple values separated by `,`),
+    /// `url`, `address` (street), `city`, `region`, `postcode`, `country`, `note`
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let get = |key: &str| optional(fields, key).map(|v| v.trim().to_string());
+        let list = |key: &str| -> Vec<String> {
+            optional(fields, key)
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        // URL and vCard 4 phone URI are written without escaping, line break would add a property
+        let url = get("url");
+        let phones = list("phone");
+        if let Some(value) = url
+            .iter()
+            .chain(phones.iter())
+            .find(|v| v.contains(['\r', '\n']))
+        {
+            return Err(format!(
+                "Line break is not allowed in URL or phone: {:?}",
+                value
+            ));
+        }
+
+        Ok(Self {
+            name: required(fields, "name")?.trim().to_string(),
+            family_name: get("family"),
+            given_name: get("given"),
+            org: get("org"),
+            title: get("title"),
+            phones,
+            emails: list("email"),
+            url,
+            address: Address {
+                street: get("address"),
+                city: get("city"),
+                region: get("region"),
+                postcode: get("postcode"),
+                country: get("country"),
+            },
+            note: get("note"),
+        })
+    }
+
+    // Family and given name, split from formatted name when not set
+    fn name_parts(&self) -> (String, String) {
+        if self.family_name.is_some() || self.given_name.is_some() {
+            return (
+                self.family_name.clone().unwrap_or_default(),
+                self.given_name.clone().unwrap_or_default(),
+            );
+        }
+        match self.name.rsplit_once(' ') {
+            Some((given, family)) => (family.to_string(), given.trim().to_string()),
+            None => (self.name.clone(), String::new()),
+        }
+    }
+
+    pub fn to_vcard(&self, version: VCardVersion) -> String {
+        let (family, given) = self.name_parts();
+        let mut lines: Vec<String> = vec![
+            "BEGIN:VCARD".to_string(),
+            match version {
+                VCardVersion::V3 => "VERSION:3.0".to_string(),
+                VCardVersion::V4 => "VERSION:4.0".to_string(),
+            },
+            format!("N:{};{};;;", escape_vcard(&family), escape_vcard(&given)),
+            format!("FN:{}", escape_vcard(&self.name)),
+        ];
+
+        if let Some(org) = &self.org {
+            lines.push(format!("ORG:{}", escape_vcard(org)));
+        }
+        if let Some(title) = &self.title {
+            lines.push(format!("TITLE:{}", escape_vcard(title)));
+        }
+        for phone in &self.phones {
+            lines.push(match version {
+                VCardVersion::V3 => format!("TEL;TYPE=VOICE:{}", escape_vcard(phone)),
+                VCardVersion::V4 => format!("TEL;VALUE=uri:tel:{}", phone.replace(' ', "-")),
+            });
+        }
+        for email in &self.emails {
+            lines.push(match version {
+                VCardVersion::V3 => format!("EMAIL;TYPE=INTERNET:{}", escape_vcard(email)),
+                VCardVersion::V4 => format!("EMAIL:{}", escape_vcard(email)),
+            });
+        }
+        if let Some(url) = &self.url {
+            lines.push(format!("URL:{}", url));
+        }
+        if !self.address.is_empty() {
+            let parts: Vec<String> = self
+                .address
+                .parts()
+                .iter()
+                .map(|p| escape_vcard(p.as_deref().unwrap_or_default()))
+                .collect();
+            // PO box; extended address; street; locality; region; postal code; country
+            lines.push(format!("ADR:;;{}", parts.join(";")));
+        }
+        if let Some(note) = &self.note {
+            lines.push(format!("NOTE:{}", escape_vcard(note)));
+        }
+        lines.push("END:VCARD".to_string());
+
+        lines
+            .iter()
+            .map(|line| fold_line(line))
+            .collect::<Vec<String>>()
+            .join("\r\n")
+    }
+
+    pub fn to_mecard(&self) -> String {
+        let mut output = String::from("MECARD:");
+
+        let name = match (&self.family_name, &self.given_name) {
+            (None, None) => escape_mecard(&self.name),
+            (family, given) => format!(
+                "{},{}",
+                escape_mecard(family.as_deref().unwrap_or_default()),
+                escape_mecard(given.as_deref().unwrap_or_default())
+            ),
+        };
+        output.push_str(&format!("N:{};", name));
+
+        if let Some(org) = &self.org {
+            output.push_str(&format!("ORG:{};", escape_mecard(org)));
+        }
+        for phone in &self.phones {
+            output.push_str(&format!("TEL:{};", escape_mecard(phone)));
+        }
+        for email in &self.emails {
+            output.push_str(&format!("EMAIL:{};", escape_mecard(email)));
+        }
+        if let Some(url) = &self.url {
+            output.push_str(&format!("URL:{};", escape_mecard(url)));
+        }
+        if !self.address.is_empty() {
+            let parts: Vec<&str> = self
+                .address
+                .parts()
+                .iter()
+                .filter_map(|p| p.as_deref())
+                .collect();
+            output.push_str(&format!("ADR:{};", escape_mecard(&parts.join(", "))));
+        }
+        if let Some(note) = &self.note {
+            output.push_str(&format!("NOTE:{};", escape_mecard(note)));
+        }
+        output.push(';');
+        output
+    }
+}
+
+// Escape text value (RFC 6350 section 3.4)
+pub(crate) fn escape_vcard(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                output.push('\\');
+                output.push(c);
+            }
+            '\n' => output.push_str("\\n"),
+            '\r' => {}
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+fn escape_mecard(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
+
+// Fold line longer than 75 octets, without splitting a character (RFC 6350 section 3.2)
+pub(crate) fn fold_line(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            output.push_str("\r\n ");
+            // Leading space counts to line length
+            length = 1;
+        }
+        output.push(c);
+        length += c.len_utf8();
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(values: &[(&str, &str)]) -> Result<Contact, String> {
+        let fields = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Contact::from_fields(&fields)
+    }
+
+    #[test]
+    fn vcard_escape() {
+        let card = contact(&[
+            ("name", "Jane Doe"),
+            ("org", "A, B; C\\D"),
+            ("note", "line 1\r\nline 2"),
+        ])
+        .unwrap()
+        .to_vcard(VCardVersion::V3);
+        assert_eq!(
+            card,
+            [
+                "BEGIN:VCARD",
+                "VERSION:3.0",
+                "N:Doe;Jane;;;",
+                "FN:Jane Doe",
+                r"ORG:A\, B\; C\\D",
+                r"NOTE:line 1\nline 2",
+                "END:VCARD",
+            ]
+            .join("\r\n")
+        );
+    }
+
+    #[test]
+    fn mecard_escape() {
+        let card = contact(&[("name", "Doe"), ("url", "https://a.example/?q=1;2")])
+            .unwrap()
+            .to_mecard();
+        assert_eq!(card, r"MECARD:N:Doe;URL:https\://a.example/?q=1\;2;;");
+    }
+
+    #[test]
+    fn line_break_rejected() {
+        assert!(contact(&[("name", "Doe"), ("url", "https://a.example\nEMAIL:x@y.z")]).is_err());
+        assert!(contact(&[("name", "Doe"), ("phone", "+1 555\r\nNOTE:x")]).is_err());
+    }
+
+    #[test]
+    fn fold_long_line() {
+        let line = format!("NOTE:{}", "é".repeat(40));
+        let folded = fold_line(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+        assert_eq!(fold_line("FN:Jane"), "FN:Jane");
+    }
+}
@@ -0,0 +1,290 @@
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+use super::uri::percent_encode;
+use super::{optional, required, Fields};
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_ALPHABET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// `bitcoin:` URI (BIP21), fields: `address`, `amount` (BTC), `label`, `message`
+pub fn bitcoin(fields: &Fields) -> Result<String, String> {
+    let address = required(fields, "address")?.trim();
+    validate_bitcoin_address(address)?;
+
+    let mut params: Vec<String> = Vec::new();
+    if let Some(amount) = optional(fields, "amount") {
+        params.push(format!("amount={}", parse_decimal(amount, 8)?));
+    }
+    for key in ["label", "message"] {
+        if let Some(value) = optional(fields, key) {
+            params.push(format!("{}={}", key, percent_encode(value)));
+        }
+    }
+
+    let mut output = format!("bitcoin:{}", address);
+    if !params.is_empty() {
+        output.push('?');
+        output.push_str(&params.join("&"));
+    }
+    Ok(output)
+}
+
+/// `ethereum:` URI (EIP-681), fields: `address`, `chain_id`, `value` (ETH),
+/// or ERC-20 transfer with `token` (contract address) and `token_amount` (smallest unit)
+pub fn ethereum(fields: &Fields) -> Result<String, String> {
+    let address = checksum_ethereum_address(required(fields, "address")?)?;
+    let chain_id = match optional(fields, "chain_id") {
+        Some(id) => {
+            let id = id
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid chain_id: \"{}\"", id))?;
+            format!("@{}", id)
+        }
+        None => String::new(),
+    };
+
+    match optional(fields, "token") {
+        Some(token) => {
+            let token = checksum_ethereum_address(token)?;
+            let amount = required(fields, "token_amount")?;
+            if amount.is_empty() || !amount.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("Invalid token_amount: \"{}\"", amount));
+            }
+            Ok(format!(
+                "ethereum:{}{}/transfer?address={}&uint256={}",
+                token, chain_id, address, amount
+            ))
+        }
+        None => {
+            let mut output = format!("ethereum:{}{}", address, chain_id);
+            if let Some(value) = optional(fields, "value") {
+                output.push_str(&format!("?value={}", to_smallest_unit(value, 18)?));
+            }
+            Ok(output)
+        }
+    }
+}
+
+// Validate positive decimal number with max decimal places
+fn parse_decimal(value: &str, decimals: usize) -> Result<String, String> {
+    let value = value.trim();
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let valid = !integer.is_empty()
+        && integer.chars().all(|c| c.is_ascii_digit())
+        && fraction.chars().all(|c| c.is_ascii_digit())
+        && fraction.len() <= decimals
+        && value.chars().any(|c| c.is_ascii_digit() && c != '0');
+    if !valid {
+        return Err(format!(
+            "Invalid amount (max {} decimals): \"{}\"",
+            decimals, value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+// Convert decimal amount to integer of smallest unit, e.g. ETH to wei
+fn to_smallest_unit(value: &str, decimals: usize) -> Result<String, String> {
+    let value = parse_decimal(value, decimals)?;
+    let (integer, fraction) = value.split_once('.').unwrap_or((&value, ""));
+    let output = format!("{}{:0<width$}", integer, fraction, width = decimals);
+    Ok(output.trim_start_matches('0').to_string())
+}
+
+/// Validate Base58Check (P2PKH, P2SH) or Bech32/Bech32m (SegWit) address, mainnet or testnet
+pub fn validate_bitcoin_address(address: &str) -> Result<(), String> {
+    let valid = match address.get(..3).map(|p| p.to_lowercase()) {
+        Some(prefix) if prefix == "bc1" || prefix == "tb1" => validate_segwit_address(address),
+        _ => validate_base58_address(address),
+    };
+    if !valid {
+        return Err(format!("Invalid bitcoin address: \"{}\"", address));
+    }
+    Ok(())
+}
+
+fn validate_base58_address(address: &str) -> bool {
+    // Decode base58 to bytes (big-endian)
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in address.bytes() {
+        let Some(mut carry) = BASE58_ALPHABET
+            .iter()
+            .position(|a| *a == c)
+            .map(|v| v as u32)
+        else {
+            return false;
+        };
+        for byte in bytes.iter_mut().rev() {
+            carry += *byte as u32 * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, (carry & 0xff) as u8);
+            carry >>= 8;
+        }
+    }
+    // Leading `1` are leading zero bytes
+    let zeros = address.bytes().take_while(|c| *c == b'1').count();
+    let mut decoded = vec![0u8; zeros];
+    decoded.extend(bytes);
+
+    if decoded.len() != 25 || !matches!(decoded[0], 0x00 | 0x05 | 0x6f | 0xc4) {
+        return false;
+    }
+    let checksum = Sha256::digest(Sha256::digest(&decoded[..21]));
+    checksum[..4] == decoded[21..]
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ *value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+// BIP173 (witness version 0) and BIP350 (witness version 1+)
+fn validate_segwit_address(address: &str) -> bool {
+    if address.len() > 90
+        || (address.to_lowercase() != address && address.to_uppercase() != address)
+    {
+        return false;
+    }
+    let address = address.to_lowercase();
+    // Human-readable part of mainnet or testnet only
+    let Some((hrp, data)) = address
+        .rsplit_once('1')
+        .filter(|(hrp, _)| *hrp == "bc" || *hrp == "tb")
+    else {
+        return false;
+    };
+    let Some(data) = data
+        .bytes()
+        .map(|c| {
+            BECH32_ALPHABET
+                .iter()
+                .position(|a| *a == c)
+                .map(|v| v as u8)
+        })
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+    if data.len() < 7 {
+        return false;
+    }
+
+    let mut values: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|c| c & 0x1f));
+    values.extend(&data);
+
+    let version = data[0];
+    let constant = if version == 0 { 1 } else { 0x2bc830a3 };
+    if version > 16 || bech32_polymod(&values) != constant {
+        return false;
+    }
+
+    // Witness program from 5-bit groups to bytes
+    let program_bits = (data.len() - 7) * 5;
+    let program_length = program_bits / 8;
+    let padding_valid = program_bits % 8 < 5;
+    padding_valid
+        && (2..=40).contains(&program_length)
+        && (version != 0 || program_length == 20 || program_length == 32)
+}
+
+/// Validate Ethereum address and return with EIP-55 checksum,
+/// mixed case address must have valid checksum
+pub fn checksum_ethereum_address(address: &str) -> Result<String, String> {
+    let error = || format!("Invalid ethereum address: \"{}\"", address);
+    let hex = address.trim().strip_prefix("0x").ok_or_else(error)?;
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(error());
+    }
+
+    let lower = hex.to_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    let mixed_case = hex != lower && hex != hex.to_uppercase();
+    if mixed_case && hex != checksummed {
+        return Err(format!(
+            "Invalid ethereum address checksum: \"{}\"",
+            address
+        ));
+    }
+    Ok(format!("0x{}", checksummed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // EIP-55 examples
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn ethereum_checksum() {
+        for address in CHECKSUMMED {
+            assert_eq!(checksum_ethereum_address(address).unwrap(), address);
+            assert_eq!(
+                checksum_ethereum_address(&address.to_lowercase().replace("0X", "0x")).unwrap(),
+                address
+            );
+        }
+        assert!(checksum_ethereum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD").is_err());
+        assert!(checksum_ethereum_address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_err());
+    }
+
+    #[test]
+    fn bitcoin_addresses() {
+        for address in [
+            // Base58Check P2PKH and P2SH
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+            // Bech32 (BIP173) and Bech32m (BIP350)
+            "BC1QAR0SRRR7XFKVY5L643LYDNW9RE59GTZZWF5MDQ",
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+        ] {
+            assert!(validate_bitcoin_address(address).is_ok(), "{}", address);
+        }
+        for address in [
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3",
+            // Mixed case, bech32 checksum for version 1, bech32m checksum for version 0
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwF5mdq",
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qweqhyr",
+            // Valid checksum for human-readable part other than `bc`/`tb`
+            "bc1xyz1qar0srrr7xfkvy5l643lydnw9re59gtzzwwrnnj",
+        ] {
+            assert!(validate_bitcoin_address(address).is_err(), "{}", address);
+        }
+    }
+}
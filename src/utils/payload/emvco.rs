@@ -0,0 +1,298 @@
+use super::{optional, required, Fields};
+
+/// PromptPay application ID (credit transfer)
+const PROMPTPAY_AID: &str = "A000000677010111";
+
+/// EMVCo merchant-presented QR payload
+#[derive(Debug, Clone, Default)]
+pub struct Emvco {
+    /// Merchant account information ID (02-51)
+    pub account_tag: u8,
+    /// Merchant account information sub fields (ID, value)
+    pub account: Vec<(u8, String)>,
+    pub mcc: Option<String>,
+    /// ISO 4217 numeric currency code
+    pub currency: String,
+    pub amount: Option<String>,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: String,
+    pub merchant_name: Option<String>,
+    pub merchant_city: Option<String>,
+    pub postal_code: Option<String>,
+    pub bill_number: Option<String>,
+    pub reference: Option<String>,
+    pub terminal: Option<String>,
+}
+
+impl Emvco {
+    /// Fields: `aid`, `account`, `account_tag` (default: 26), `mcc` (default: 0000), `currency`, `country`,
+    /// `amount`, `name`, `city`, `postal_code`, `bill`, `reference`, `terminal`
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let account_tag = match optional(fields, "account_tag") {
+            Some(v) => v
+                .parse::<u8>()
+                .ok()
+                .filter(|t| (2..=51).contains(t))
+                .ok_or(format!("Invalid merchant account tag (02-51): \"{}\"", v))?,
+            None => 26,
+        };
+
+        let mut emvco = Self {
+            account_tag,
+            account: vec![
+                (0, required(fields, "aid")?.to_string()),
+                (1, required(fields, "account")?.to_string()),
+            ],
+            mcc: Some(optional(fields, "mcc").unwrap_or("0000").to_string()),
+            currency: required(fields, "currency")?.to_string(),
+            country: required(fields, "country")?.to_uppercase(),
+            merchant_name: Some(required(fields, "name")?.to_string()),
+            merchant_city: Some(required(fields, "city")?.to_string()),
+            ..Default::default()
+        };
+        emvco.set_common_fields(fields)?;
+        Ok(emvco)
+    }
+
+    /// PromptPay credit transfer, fields: `id` (mobile number, national ID/tax ID or e-wallet ID), `amount`,
+    /// and optional `mcc`, `name`, `city`, `postal_code`, `bill`, `reference`, `terminal`
+    pub fn promptpay_from_fields(fields: &Fields) -> Result<Self, String> {
+        let id = required(fields, "id")?;
+        let digits: String = id.chars().filter(|c| c.is_ascii_digit()).collect();
+
+        let account_id = match digits.len() {
+            // Mobile number in 0066 format
+            10 if digits.starts_with('0') => (1, format!("0066{}", &digits[1..])),
+            11 if digits.starts_with("66") => (1, format!("00{}", digits)),
+            13 if digits.starts_with("0066") => (1, digits),
+            // National ID or tax ID
+            13 => (2, digits),
+            // E-wallet ID
+            15 => (3, digits),
+            _ => return Err(format!("Invalid PromptPay ID: \"{}\"", id)),
+        };
+
+        let mut emvco = Self {
+            account_tag: 29,
+            account: vec![(0, PROMPTPAY_AID.to_string()), account_id],
+            mcc: optional(fields, "mcc").map(String::from),
+            currency: "764".to_string(),
+            country: "TH".to_string(),
+            merchant_name: optional(fields, "name").map(String::from),
+            merchant_city: optional(fields, "city").map(String::from),
+            ..Default::default()
+        };
+        emvco.set_common_fields(fields)?;
+        Ok(emvco)
+    }
+
+    fn set_common_fields(&mut self, fields: &Fields) -> Result<(), String> {
+        self.amount = optional(fields, "amount").map(parse_amount).transpose()?;
+        self.postal_code = optional(fields, "postal_code").map(String::from);
+        self.bill_number = optional(fields, "bill").map(String::from);
+        self.reference = optional(fields, "reference").map(String::from);
+        self.terminal = optional(fields, "terminal").map(String::from);
+        Ok(())
+    }
+
+    pub fn build(&self) -> Result<String, String> {
+        let mut output = String::new();
+
+        // Payload format indicator
+        output.push_str(&tlv(0, "01")?);
+        // Point of initiation method, static (11) or dynamic with amount (12)
+        output.push_str(&tlv(1, if self.amount.is_some() { "12" } else { "11" })?);
+
+        let mut account = String::new();
+        for (id, value) in &self.account {
+            account.push_str(&tlv(*id, value)?);
+        }
+        output.push_str(&tlv(self.account_tag, &account)?);
+
+        if let Some(mcc) = &self.mcc {
+            check_format("merchant category code", mcc, 4, true)?;
+            output.push_str(&tlv(52, mcc)?);
+        }
+        check_format("currency", &self.currency, 3, true)?;
+        output.push_str(&tlv(53, &self.currency)?);
+        if let Some(amount) = &self.amount {
+            output.push_str(&tlv(54, amount)?);
+        }
+        check_format("country", &self.country, 2, false)?;
+        output.push_str(&tlv(58, &self.country)?);
+        if let Some(name) = &self.merchant_name {
+            check_length("merchant name", name, 25)?;
+            output.push_str(&tlv(59, name)?);
+        }
+        if let Some(city) = &self.merchant_city {
+            check_length("merchant city", city, 15)?;
+            output.push_str(&tlv(60, city)?);
+        }
+        if let Some(postal_code) = &self.postal_code {
+            check_length("postal code", postal_code, 10)?;
+            output.push_str(&tlv(61, postal_code)?);
+        }
+
+        // Additional data field template
+        let mut additional = String::new();
+        for (id, value) in [
+            (1, &self.bill_number),
+            (5, &self.reference),
+            (7, &self.terminal),
+        ] {
+            if let Some(value) = value {
+                check_length("additional data", value, 25)?;
+                additional.push_str(&tlv(id, value)?);
+            }
+        }
+        if !additional.is_empty() {
+            output.push_str(&tlv(62, &additional)?);
+        }
+
+        // CRC covers all data including its own ID and length
+        output.push_str("6304");
+        let crc = crc16_ccitt(output.as_bytes());
+        output.push_str(&format!("{:04X}", crc));
+
+        Ok(output)
+    }
+}
+
+// ID, length (2 digits) and value
+fn tlv(id: u8, value: &str) -> Result<String, String> {
+    let length = value.chars().count();
+    if length > 99 {
+        return Err(format!(
+            "Value of ID {:02} is longer than 99: \"{}\"",
+            id, value
+        ));
+    }
+    Ok(format!("{:02}{:02}{}", id, length, value))
+}
+
+fn check_length(name: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.chars().count() > max {
+        return Err(format!("{} is longer than {}: \"{}\"", name, max, value));
+    }
+    Ok(())
+}
+
+fn check_format(name: &str, value: &str, length: usize, numeric: bool) -> Result<(), String> {
+    let valid = value.len() == length
+        && value.chars().all(|c| {
+            if numeric {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        });
+    if !valid {
+        return Err(format!("Invalid {}: \"{}\"", name, value));
+    }
+    Ok(())
+}
+
+// Amount with `.` as decimal separator, `,` is rejected as it can be decimal or thousands separator
+fn parse_amount(value: &str) -> Result<String, String> {
+    let amount = Some(value.trim())
+        .filter(|v| v.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|a| a.is_finite() && *a > 0.0)
+        .ok_or(format!("Invalid amount: \"{}\"", value))?;
+    let amount = format!("{:.2}", amount);
+    check_length("amount", &amount, 13)?;
+    Ok(amount)
+}
+
+/// CRC-16/CCITT-FALSE (polynomial 0x1021, initial 0xFFFF)
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29b1);
+        assert_eq!(crc16_ccitt(b""), 0xffff);
+    }
+
+    fn promptpay(values: &[(&str, &str)]) -> Result<String, String> {
+        let fields = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        super::super::build("promptpay", &fields).map(|p| p.content)
+    }
+
+    // Top level data objects (ID, value) without CRC
+    fn data_objects(payload: &str) -> Vec<(String, String)> {
+        let mut objects = Vec::new();
+        let mut rest = payload;
+        while rest.len() >= 4 {
+            let length: usize = rest[2..4].parse().unwrap();
+            objects.push((rest[..2].to_string(), rest[4..4 + length].to_string()));
+            rest = &rest[4 + length..];
+        }
+        objects.retain(|(id, _)| id != "63");
+        objects.sort();
+        objects
+    }
+
+    fn check_crc(payload: &str) {
+        let (data, crc) = payload.split_at(payload.len() - 4);
+        assert_eq!(format!("{:04X}", crc16_ccitt(data.as_bytes())), crc);
+    }
+
+    #[test]
+    fn promptpay_published_vectors() {
+        // Vectors of promptpay-qr (github.com/dtinth/promptpay-qr), data objects can be in any order
+        let vectors = [
+            (
+                "0801234567",
+                None,
+                "00020101021129370016A000000677010111011300668012345675802TH530376463046197",
+            ),
+            (
+                "000-000-0000",
+                Some("4.22"),
+                "00020101021229370016A000000677010111011300660000000005802TH530376454044.226304E469",
+            ),
+            (
+                "1111111111111",
+                None,
+                "00020101021129370016A000000677010111021311111111111115802TH530376463047B5A",
+            ),
+        ];
+        for (id, amount, expected) in vectors {
+            check_crc(expected);
+            let mut values = vec![("id", id)];
+            values.extend(amount.map(|a| ("amount", a)));
+            let payload = promptpay(&values).unwrap();
+            check_crc(&payload);
+            assert_eq!(data_objects(&payload), data_objects(expected));
+        }
+    }
+
+    #[test]
+    fn amount_format() {
+        assert_eq!(parse_amount("12.5").unwrap(), "12.50");
+        assert!(parse_amount("1,5").is_err());
+        assert!(parse_amount("1,000.00").is_err());
+        assert!(parse_amount("1e3").is_err());
+        assert!(parse_amount("0").is_err());
+    }
+}
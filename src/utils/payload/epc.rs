@@ -0,0 +1,196 @@
+use super::{optional, required, Fields};
+
+/// EPC069-12 SEPA credit transfer (GiroCode)
+#[derive(Debug, Clone, Default)]
+pub struct Epc {
+    pub bic: Option<String>,
+    pub name: String,
+    pub iban: String,
+    /// Amount in euro (2 decimals)
+    pub amount: Option<String>,
+    pub purpose: Option<String>,
+    /// Structured creditor reference (ISO 11649)
+    pub reference: Option<String>,
+    /// Unstructured remittance information
+    pub text: Option<String>,
+    pub information: Option<String>,
+}
+
+impl Epc {
+    /// Fields: `name`, `iban`, `bic`, `amount`, `purpose`, `reference` or `text`, `information`
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let epc = Self {
+            bic: optional(fields, "bic").map(validate_bic).transpose()?,
+            name: required(fields, "name")?.trim().to_string(),
+            iban: validate_iban(required(fields, "iban")?)?,
+            amount: optional(fields, "amount")
+                .map(|v| parse_amount(v, 999_999_999.99))
+                .transpose()?,
+            purpose: optional(fields, "purpose").map(|v| v.to_uppercase()),
+            reference: optional(fields, "reference").map(|v| v.replace(' ', "").to_uppercase()),
+            text: optional(fields, "text").map(String::from),
+            information: optional(fields, "information").map(String::from),
+        };
+
+        check_length("name", &epc.name, 70)?;
+        if let Some(purpose) = &epc.purpose {
+            if purpose.len() != 4 || !purpose.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!("Invalid purpose code: \"{}\"", purpose));
+            }
+        }
+        match (&epc.reference, &epc.text) {
+            (Some(_), Some(_)) => {
+                return Err("Only one of reference or text can be set".to_string());
+            }
+            (Some(reference), None) => {
+                check_length("reference", reference, 35)?;
+                validate_creditor_reference(reference)?;
+            }
+            (None, Some(text)) => check_length("text", text, 140)?,
+            (None, None) => {}
+        }
+        if let Some(information) = &epc.information {
+            check_length("information", information, 70)?;
+        }
+
+        Ok(epc)
+    }
+
+    pub fn build(&self) -> Result<String, String> {
+        let lines = [
+            "BCD",
+            // Version 002 (BIC is optional), character set UTF-8
+            "002",
+            "1",
+            "SCT",
+            self.bic.as_deref().unwrap_or_default(),
+            &self.name,
+            &self.iban,
+            &self
+                .amount
+                .as_ref()
+                .map(|a| format!("EUR{}", a))
+                .unwrap_or_default(),
+            self.purpose.as_deref().unwrap_or_default(),
+            self.reference.as_deref().unwrap_or_default(),
+            self.text.as_deref().unwrap_or_default(),
+            self.information.as_deref().unwrap_or_default(),
+        ];
+
+        // Trailing empty lines are omitted
+        let count = lines.iter().rposition(|l| !l.is_empty()).unwrap_or(0) + 1;
+        let output = lines[..count].join("\n");
+
+        if output.len() > 331 {
+            return Err(format!(
+                "EPC payload is longer than 331 bytes: {}",
+                output.len()
+            ));
+        }
+        Ok(output)
+    }
+}
+
+pub(crate) fn check_length(name: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.chars().count() > max {
+        return Err(format!("{} is longer than {}: \"{}\"", name, max, value));
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_amount(value: &str, max: f64) -> Result<String, String> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|a| *a >= 0.01 && *a <= max)
+        .map(|a| format!("{:.2}", a))
+        .ok_or(format!("Invalid amount: \"{}\"", value))
+}
+
+// Remainder of large number in string form (ISO 7064 MOD 97-10)
+fn mod97(value: &str) -> Result<u32, String> {
+    let mut remainder: u32 = 0;
+    for c in value.chars() {
+        let digit = c
+            .to_digit(36)
+            .ok_or(format!("Invalid character: \"{}\"", c))?;
+        remainder = if digit < 10 {
+            (remainder * 10 + digit) % 97
+        } else {
+            (remainder * 100 + digit) % 97
+        };
+    }
+    Ok(remainder)
+}
+
+/// Validate IBAN check digits, return IBAN without spaces in uppercase
+pub fn validate_iban(value: &str) -> Result<String, String> {
+    let iban: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+
+    // ASCII check first, slicing below needs one byte per character
+    let valid = (15..=34).contains(&iban.len())
+        && iban.chars().all(|c| c.is_ascii_alphanumeric())
+        && iban[..2].chars().all(|c| c.is_ascii_uppercase())
+        && mod97(&format!("{}{}", &iban[4..], &iban[..4])) == Ok(1);
+
+    if !valid {
+        return Err(format!("Invalid IBAN: \"{}\"", value));
+    }
+    Ok(iban)
+}
+
+/// Validate creditor reference (ISO 11649, RF...)
+pub fn validate_creditor_reference(value: &str) -> Result<(), String> {
+    let valid = value.len() > 4
+        && value.len() <= 25
+        && value.starts_with("RF")
+        && value.chars().all(|c| c.is_ascii_alphanumeric())
+        && mod97(&format!("{}{}", &value[4..], &value[..4])) == Ok(1);
+
+    if !valid {
+        return Err(format!("Invalid creditor reference: \"{}\"", value));
+    }
+    Ok(())
+}
+
+fn validate_bic(value: &str) -> Result<String, String> {
+    let bic = value.trim().to_uppercase();
+    if !matches!(bic.len(), 8 | 11) || !bic.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid BIC: \"{}\"", value));
+    }
+    Ok(bic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iban_mod97() {
+        assert_eq!(
+            validate_iban("gb82 west 1234 5698 7654 32").unwrap(),
+            "GB82WEST12345698765432"
+        );
+        assert!(validate_iban("DE89370400440532013000").is_ok());
+        assert!(validate_iban("DE89370400440532013001").is_err());
+        assert!(validate_iban("1289370400440532013000").is_err());
+    }
+
+    #[test]
+    fn iban_non_ascii() {
+        assert!(validate_iban("A\u{e9}1234567890123456").is_err());
+        assert!(validate_iban("\u{e9}\u{e9}1234567890123456").is_err());
+    }
+
+    #[test]
+    fn creditor_reference_mod97() {
+        assert!(validate_creditor_reference("RF18539007547034").is_ok());
+        assert!(validate_creditor_reference("RF19539007547034").is_err());
+        assert!(validate_creditor_reference("RF\u{e9}8539007547034").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::NaiveDate;
+
+use super::contact::{escape_vcard, fold_line};
+use super::{optional, required, Fields};
+
+/// iCalendar event (VEVENT)
+#[derive(Debug, Clone, Default)]
+pub struct Event {
+    pub summary: String,
+    /// Date (`YYYYMMDD`) or date-time (`YYYYMMDDTHHMMSS`, `Z` suffix for UTC)
+    pub start: String,
+    pub end: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+}
+
+impl Event {
+    /// Fields: `summary`, `start`, `end` (`YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]`, `Z` suffix for UTC),
+    /// `location`, `description`, `url`
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let start = parse_date_time(required(fields, "start")?)?;
+        let end = optional(fields, "end").map(parse_date_time).transpose()?;
+
+        if let Some(end) = &end {
+            if end.len() != start.len() {
+                return Err(
+                    "start and end must be in same format (date, local or UTC date-time)"
+                        .to_string(),
+                );
+            }
+            if end < &start {
+                return Err("end must not be before start".to_string());
+            }
+        }
+
+        // URL is written without escaping, line break would add a property
+        let url = optional(fields, "url").map(String::from);
+        if url.as_ref().is_some_and(|u| u.contains(['\r', '\n'])) {
+            return Err("Line break is not allowed in URL".to_string());
+        }
+
+        Ok(Self {
+            summary: required(fields, "summary")?.to_string(),
+            start,
+            end,
+            location: optional(fields, "location").map(String::from),
+            description: optional(fields, "description").map(String::from),
+            url,
+        })
+    }
+
+    pub fn build(&self) -> String {
+        let date_time = |name: &str, value: &String| {
+            if value.len() == 8 {
+                format!("{};VALUE=DATE:{}", name, value)
+            } else {
+                format!("{}:{}", name, value)
+            }
+        };
+
+        let mut lines = vec![
+            "BEGIN:VEVENT".to_string(),
+            format!("SUMMARY:{}", escape_vcard(&self.summary)),
+            date_time("DTSTART", &self.start),
+        ];
+        if let Some(end) = &self.end {
+            lines.push(date_time("DTEND", end));
+        }
+        if let Some(location) = &self.location {
+            lines.push(format!("LOCATION:{}", escape_vcard(location)));
+        }
+        if let Some(description) = &self.description {
+            lines.push(format!("DESCRIPTION:{}", escape_vcard(description)));
+        }
+        if let Some(url) = &self.url {
+            lines.push(format!("URL:{}", url));
+        }
+        lines.push("END:VEVENT".to_string());
+
+        lines
+            .iter()
+            .map(|line| fold_line(line))
+            .collect::<Vec<String>>()
+            .join("\r\n")
+    }
+}
+
+// Parse `YYYY-MM-DD[( |T)HH:MM[:SS]][Z]` to iCalendar format
+fn parse_date_time(value: &str) -> Result<String, String> {
+    let error = || format!("Invalid date time: \"{}\"", value);
+    let value = value.trim();
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(v) => (v, true),
+        None => (value, false),
+    };
+    let (date, time) = match value.split_once([' ', 'T']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let number = |part: &str, len: usize, range: std::ops::RangeInclusive<u32>| {
+        part.parse::<u32>()
+            .ok()
+            .filter(|n| part.len() == len && range.contains(n))
+            .ok_or_else(error)
+    };
+
+    let date_parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = date_parts[..] else {
+        return Err(error());
+    };
+    let (year, month, day) = (
+        number(year, 4, 1..=9999)?,
+        number(month, 2, 1..=12)?,
+        number(day, 2, 1..=31)?,
+    );
+    // Day must exist in month, e.g. no 02-31
+    if NaiveDate::from_ymd_opt(year as i32, month, day).is_none() {
+        return Err(error());
+    }
+    let mut output = format!("{:04}{:02}{:02}", year, month, day);
+
+    match time {
+        Some(time) => {
+            let time_parts: Vec<&str> = time.split(':').collect();
+            let (hour, minute, second) = match time_parts[..] {
+                [h, m] => (h, m, "00"),
+                [h, m, s] => (h, m, s),
+                _ => return Err(error()),
+            };
+            output.push_str(&format!(
+                "T{:02}{:02}{:02}",
+                number(hour, 2, 0..=23)?,
+                number(minute, 2, 0..=59)?,
+                number(second, 2, 0..=59)?
+            ));
+            if utc {
+                output.push('Z');
+            }
+        }
+        None if utc => return Err(error()),
+        None => {}
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(values: &[(&str, &str)]) -> Result<String, String> {
+        let fields = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Event::from_fields(&fields).map(|e| e.build())
+    }
+
+    #[test]
+    fn vevent_output() {
+        assert_eq!(
+            event(&[
+                ("summary", "Launch; v2"),
+                ("start", "2025-03-01 09:30"),
+                ("end", "2025-03-01T11:00:15"),
+                ("location", "Room 1, HQ"),
+            ])
+            .unwrap(),
+            [
+                "BEGIN:VEVENT",
+                r"SUMMARY:Launch\; v2",
+                "DTSTART:20250301T093000",
+                "DTEND:20250301T110015",
+                r"LOCATION:Room 1\, HQ",
+                "END:VEVENT",
+            ]
+            .join("\r\n")
+        );
+        assert_eq!(
+            event(&[("summary", "Day"), ("start", "2024-02-29")]).unwrap(),
+            "BEGIN:VEVENT\r\nSUMMARY:Day\r\nDTSTART;VALUE=DATE:20240229\r\nEND:VEVENT"
+        );
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(
+            parse_date_time("2025-06-01 08:00Z").unwrap(),
+            "20250601T080000Z"
+        );
+        assert!(parse_date_time("2025-02-31").is_err());
+        assert!(parse_date_time("2025-02-29").is_err());
+        assert!(parse_date_time("2025-04-31 10:00").is_err());
+        assert!(parse_date_time("2025-06-01Z").is_err());
+        assert!(event(&[
+            ("summary", "x"),
+            ("start", "2025-01-02"),
+            ("end", "2025-01-01")
+        ])
+        .is_err());
+        assert!(event(&[
+            ("summary", "x"),
+            ("start", "2025-01-02"),
+            ("end", "2025-01-03 10:00")
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn url_line_break_rejected() {
+        let url = "https://a.example\r\nATTACH:x";
+        assert!(event(&[("summary", "x"), ("start", "2025-01-02"), ("url", url)]).is_err());
+    }
+}
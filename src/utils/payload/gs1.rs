@@ -0,0 +1,290 @@
+use super::uri::percent_encode;
+use super::{optional, Fields};
+
+/// Group separator, used as FNC1 after variable length element
+pub const GS: char = '\u{1d}';
+
+/// Named fields and application identifiers, in output order
+const NAMED_FIELDS: [(&str, &str); 7] = [
+    ("gtin", "01"),
+    ("production_date", "11"),
+    ("best_before", "15"),
+    ("expiry", "17"),
+    ("cpv", "22"),
+    ("batch", "10"),
+    ("serial", "21"),
+];
+
+/// GS1 element strings (application identifier, value)
+#[derive(Debug, Clone, Default)]
+pub struct Gs1 {
+    pub elements: Vec<(String, String)>,
+}
+
+impl Gs1 {
+    /// Fields: `gtin`, `batch`, `serial`, `expiry`, `best_before`, `production_date` (`YYYY-MM-DD` or `YYMMDD`),
+    /// `cpv`, or any application identifier as key (e.g. `400`)
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let mut elements: Vec<(String, String)> = Vec::new();
+
+        for (name, ai) in NAMED_FIELDS {
+            if let Some(value) = optional(fields, name) {
+                elements.push((ai.to_string(), value.trim().to_string()));
+            }
+        }
+
+        let mut ai_fields: Vec<(&String, &String)> = fields
+            .iter()
+            .filter(|(key, value)| key.chars().all(|c| c.is_ascii_digit()) && !value.is_empty())
+            .collect();
+        ai_fields.sort();
+        for (ai, value) in ai_fields {
+            if elements.iter().any(|(a, _)| a == ai) {
+                return Err(format!("Duplicate application identifier: ({})", ai));
+            }
+            elements.push((ai.clone(), value.trim().to_string()));
+        }
+
+        for (ai, value) in elements.iter_mut() {
+            *value = validate_element(ai, value)?;
+        }
+
+        // Fixed length elements first, they don't need separator
+        elements.sort_by_key(|(ai, _)| fixed_length(ai).is_none());
+
+        Ok(Self { elements })
+    }
+
+    /// Element string with FNC1 (GS) separator after variable length elements
+    pub fn element_string(&self) -> String {
+        let mut output = String::new();
+        for (index, (ai, value)) in self.elements.iter().enumerate() {
+            output.push_str(ai);
+            output.push_str(value);
+            if fixed_length(ai).is_none() && index + 1 < self.elements.len() {
+                output.push(GS);
+            }
+        }
+        output
+    }
+
+    /// Human readable interpretation, e.g. `(01)09501101530003(10)AB-123`
+    pub fn human_readable(&self) -> String {
+        self.elements
+            .iter()
+            .map(|(ai, value)| format!("({}){}", ai, value))
+            .collect()
+    }
+
+    /// GS1 Digital Link URI, fields: `domain` (default: https://id.gs1.org)
+    pub fn digital_link(&self, fields: &Fields) -> Result<String, String> {
+        let domain = optional(fields, "domain")
+            .unwrap_or("https://id.gs1.org")
+            .trim_end_matches('/');
+
+        let gtin = self
+            .elements
+            .iter()
+            .find(|(ai, _)| ai == "01")
+            .map(|(_, v)| v)
+            .ok_or("Payload field \"gtin\" is required")?;
+        let mut output = format!("{}/01/{}", domain, gtin);
+
+        // Key qualifiers in path, in order
+        for qualifier in ["22", "10", "21"] {
+            if let Some((_, value)) = self.elements.iter().find(|(ai, _)| ai == qualifier) {
+                output.push_str(&format!("/{}/{}", qualifier, percent_encode(value)));
+            }
+        }
+
+        let attributes: Vec<String> = self
+            .elements
+            .iter()
+            .filter(|(ai, _)| !matches!(ai.as_str(), "01" | "22" | "10" | "21"))
+            .map(|(ai, value)| format!("{}={}", ai, percent_encode(value)))
+            .collect();
+        if !attributes.is_empty() {
+            output.push('?');
+            output.push_str(&attributes.join("&"));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Build payload type `gs1` (element string) or `gs1_digital_link`
+pub fn build(fields: &Fields, digital_link: bool) -> Result<String, String> {
+    let gs1 = Gs1::from_fields(fields)?;
+    if gs1.elements.is_empty() {
+        return Err("At least one GS1 element is required".to_string());
+    }
+    if digital_link {
+        gs1.digital_link(fields)
+    } else {
+        Ok(gs1.element_string())
+    }
+}
+
+// Predefined fixed length of element value by first 2 digits of AI
+fn fixed_length(ai: &str) -> Option<usize> {
+    match ai.get(..2)? {
+        "00" => Some(18),
+        "01" | "02" | "03" => Some(14),
+        "04" => Some(16),
+        "11" | "12" | "13" | "14" | "15" | "16" | "17" | "18" | "19" => Some(6),
+        "20" => Some(2),
+        "31" | "32" | "33" | "34" | "35" | "36" => Some(6),
+        "41" => Some(13),
+        _ => None,
+    }
+}
+
+// Length of AI by first 2 digits
+fn ai_length(ai: &str) -> Option<usize> {
+    let prefix: u32 = ai.get(..2)?.parse().ok()?;
+    match prefix {
+        0..=22 | 30 | 37 | 90..=99 => Some(2),
+        23..=25 | 40..=42 => Some(3),
+        _ => Some(4),
+    }
+}
+
+// Maximum length and numeric only of variable length element value (GS1 General Specifications),
+// other AIs are checked against the 90 characters limit of element string
+fn variable_length(ai: &str) -> (usize, bool) {
+    match ai {
+        "10" | "21" | "22" | "254" | "420" => (20, false),
+        "30" | "37" => (8, true),
+        "240" | "241" | "250" | "251" | "253" | "400" | "401" | "403" | "8004" | "90" => {
+            (30, false)
+        }
+        "421" => (12, false),
+        "710" | "711" | "712" | "713" | "714" | "715" => (20, false),
+        "8020" => (25, false),
+        _ => (90, false),
+    }
+}
+
+fn validate_element(ai: &str, value: &str) -> Result<String, String> {
+    if ai_length(ai) != Some(ai.len()) {
+        return Err(format!("Invalid application identifier: ({})", ai));
+    }
+
+    let value = match ai {
+        "01" | "02" => validate_gtin(value)?,
+        "11" | "12" | "13" | "15" | "16" | "17" => parse_date(value)?,
+        _ => value.to_string(),
+    };
+
+    match fixed_length(ai) {
+        Some(length) => {
+            if value.len() != length || !value.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("({}) must be {} digits: \"{}\"", ai, length, value));
+            }
+        }
+        None => {
+            let (max, numeric) = variable_length(ai);
+            if value.chars().count() > max {
+                return Err(format!("({}) is longer than {}: \"{}\"", ai, max, value));
+            }
+            // GS1 AI encodable character set 82 (printable ASCII subset)
+            let valid = !value.is_empty()
+                && value.chars().all(|c| {
+                    if numeric {
+                        c.is_ascii_digit()
+                    } else {
+                        c.is_ascii_graphic() && !"#$@[\\]^`{|}~".contains(c)
+                    }
+                });
+            if !valid {
+                return Err(format!("Invalid value of ({}): \"{}\"", ai, value));
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+/// Validate GTIN-8/12/13/14 check digit, return as GTIN-14
+pub fn validate_gtin(value: &str) -> Result<String, String> {
+    let gtin = value.trim();
+    let valid = matches!(gtin.len(), 8 | 12 | 13 | 14)
+        && gtin.chars().all(|c| c.is_ascii_digit())
+        && check_digit(&gtin[..gtin.len() - 1]) == gtin[gtin.len() - 1..].parse().ok();
+    if !valid {
+        return Err(format!("Invalid GTIN: \"{}\"", value));
+    }
+    Ok(format!("{:0>14}", gtin))
+}
+
+/// GS1 mod 10 check digit, weight 3 and 1 from the right
+pub fn check_digit(digits: &str) -> Option<u32> {
+    let sum = digits
+        .chars()
+        .rev()
+        .enumerate()
+        .map(|(i, c)| c.to_digit(10).map(|d| if i % 2 == 0 { d * 3 } else { d }))
+        .sum::<Option<u32>>()?;
+    Some((10 - sum % 10) % 10)
+}
+
+// `YYYY-MM-DD` or `YYMMDD` to `YYMMDD`
+fn parse_date(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let date = match value.split('-').collect::<Vec<&str>>()[..] {
+        [year, month, day]
+            if year.len() == 4 && year.is_ascii() && month.len() == 2 && day.len() == 2 =>
+        {
+            format!("{}{}{}", &year[2..], month, day)
+        }
+        _ => value.to_string(),
+    };
+    let valid = date.len() == 6
+        && date.chars().all(|c| c.is_ascii_digit())
+        && (1..=12).contains(&date[2..4].parse::<u32>().unwrap_or(0))
+        && date[4..].parse::<u32>().unwrap_or(99) <= 31;
+    if !valid {
+        return Err(format!(
+            "Invalid date (YYYY-MM-DD or YYMMDD): \"{}\"",
+            value
+        ));
+    }
+    Ok(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gtin_check_digit() {
+        assert_eq!(check_digit("400638133393"), Some(1));
+        assert_eq!(check_digit("9638507"), Some(4));
+        assert_eq!(check_digit("0001234560001"), Some(2));
+        assert_eq!(check_digit("12a"), None);
+    }
+
+    #[test]
+    fn gtin_validation() {
+        assert_eq!(validate_gtin("4006381333931").unwrap(), "04006381333931");
+        assert_eq!(validate_gtin("96385074").unwrap(), "00000096385074");
+        assert!(validate_gtin("4006381333932").is_err());
+        assert!(validate_gtin("400638133393").is_err());
+    }
+
+    #[test]
+    fn variable_length_limits() {
+        assert_eq!(
+            validate_element("10", &"A".repeat(20)).unwrap(),
+            "A".repeat(20)
+        );
+        assert!(validate_element("10", &"A".repeat(21)).is_err());
+        assert!(validate_element("21", &"7".repeat(40)).is_err());
+        assert!(validate_element("400", &"P".repeat(31)).is_err());
+        assert!(validate_element("30", "123").is_ok());
+        assert!(validate_element("30", "12A").is_err());
+        assert!(validate_element("30", "123456789").is_err());
+        assert!(validate_element("99", &"X".repeat(90)).is_ok());
+        assert!(validate_element("10", "AB#1").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+pub mod contact;
+pub mod crypto;
+pub mod emvco;
+pub mod epc;
+pub mod event;
+pub mod gs1;
+pub mod otp;
+pub mod swiss_qr_bill;
+pub mod uri;
+pub mod wifi;
+
+/// Payload fields by name
+pub type Fields = HashMap<String, String>;
+
+/// QR content built from payload, with QR options required by the format
+#[derive(Debug, Clone, Default)]
+pub struct Payload {
+    pub content: String,
+    /// Error correction level (l|m|q|h) required by the format
+    pub error_correction_level: Option<String>,
+    /// Draw Swiss cross on center of QR (Swiss QR-bill)
+    pub swiss_cross: bool,
+    /// Encode QR in GS1 mode (FNC1 in first position)
+    pub gs1: bool,
+    /// Encode GS1 DataMatrix instead of QR (with `gs1`)
+    pub datamatrix: bool,
+    /// Generated values to keep in sidecar file (name, value)
+    pub generated: Vec<(String, String)>,
+}
+
+impl From<String> for Payload {
+    fn from(content: String) -> Self {
+        Self {
+            content,
+            ..Default::default()
+        }
+    }
+}
+
+/// Build QR content from payload type and fields
+pub fn build(kind: &str, fields: &Fields) -> Result<Payload, String> {
+    let content = match kind.to_lowercase().as_str() {
+        "epc" | "girocode" => {
+            return Ok(Payload {
+                content: epc::Epc::from_fields(fields)?.build()?,
+                error_correction_level: Some("m".to_string()),
+                ..Default::default()
+            })
+        }
+        "swiss_qr_bill" => {
+            return Ok(Payload {
+                content: swiss_qr_bill::SwissQrBill::from_fields(fields)?.build()?,
+                error_correction_level: Some("m".to_string()),
+                swiss_cross: true,
+                ..Default::default()
+            })
+        }
+        "vcard" => {
+            let version = contact::VCardVersion::from_fields(fields)?;
+            Ok(contact::Contact::from_fields(fields)?.to_vcard(version))
+        }
+        "emvco" => emvco::Emvco::from_fields(fields)?.build(),
+        "promptpay" => emvco::Emvco::promptpay_from_fields(fields)?.build(),
+        "gs1" => {
+            return Ok(Payload {
+                content: gs1::build(fields, false)?,
+                gs1: true,
+                ..Default::default()
+            })
+        }
+        "gs1_datamatrix" => {
+            return Ok(Payload {
+                content: gs1::build(fields, false)?,
+                gs1: true,
+                datamatrix: true,
+                ..Default::default()
+            })
+        }
+        "gs1_digital_link" => gs1::build(fields, true),
+        "totp" | "hotp" => {
+            let otp = otp::Otp::from_fields(fields, kind.eq_ignore_ascii_case("hotp"))?;
+            let generated = if otp.generated_secret {
+                vec![
+                    ("issuer".to_string(), otp.issuer.clone().unwrap_or_default()),
+                    ("account".to_string(), otp.account.clone()),
+                    ("secret".to_string(), otp.secret.clone()),
+                ]
+            } else {
+                Vec::new()
+            };
+            return Ok(Payload {
+                content: otp.build(),
+                generated,
+                ..Default::default()
+            });
+        }
+        "bitcoin" => crypto::bitcoin(fields),
+        "ethereum" => crypto::ethereum(fields),
+        "event" => Ok(event::Event::from_fields(fields)?.build()),
+        "geo" => uri::geo(fields),
+        "mailto" => uri::mailto(fields),
+        "sms" => uri::sms(fields),
+        "tel" => uri::tel(fields),
+        "mecard" => Ok(contact::Contact::from_fields(fields)?.to_mecard()),
+        "wifi" => Ok(wifi::Wifi::from_fields(fields)?.to_string()),
+        _ => Err(format!("Unknown payload type: \"{}\"", kind)),
+    };
+    content.map(Payload::from)
+}
+
+/// Parse field arguments in `key=value` format, a key can be given once
+pub fn parse_fields(args: &[String]) -> Result<Fields, String> {
+    let mut fields = Fields::new();
+    for arg in args {
+        let (key, value) = arg
+            .split_once('=')
+            .ok_or(format!("Invalid payload field (key=value): \"{}\"", arg))?;
+        let key = key.trim().to_lowercase();
+        if fields.contains_key(&key) {
+            return Err(format!("Payload field \"{}\" is given more than once", key));
+        }
+        fields.insert(key, value.to_string());
+    }
+    Ok(fields)
+}
+
+// Get field value, empty value is treated as not set
+pub(crate) fn optional<'a>(fields: &'a Fields, key: &str) -> Option<&'a str> {
+    fields
+        .get(key)
+        .map(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+}
+
+pub(crate) fn required<'a>(fields: &'a Fields, key: &str) -> Result<&'a str, String> {
+    optional(fields, key).ok_or(format!("Payload field \"{}\" is required", key))
+}
+
+pub(crate) fn flag(fields: &Fields, key: &str) -> Result<bool, String> {
+    match optional(fields, key).map(|v| v.to_lowercase()).as_deref() {
+        None | Some("false") | Some("0") | Some("no") => Ok(false),
+        Some("true") | Some("1") | Some("yes") => Ok(true),
+        Some(v) => Err(format!("Invalid value of \"{}\": \"{}\"", key, v)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_fields_key_value() {
+        let fields = parse_fields(&args(&["SSID=Home=1", " auth =wpa"])).unwrap();
+        assert_eq!(fields["ssid"], "Home=1");
+        assert_eq!(fields["auth"], "wpa");
+        assert!(parse_fields(&args(&["ssid"])).is_err());
+    }
+
+    #[test]
+    fn parse_fields_duplicate_key() {
+        assert_eq!(
+            parse_fields(&args(&["ssid=a", "SSID=b"])).unwrap_err(),
+            "Payload field \"ssid\" is given more than once"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use super::uri::percent_encode;
+use super::{optional, required, Fields};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// OTP type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OtpType {
+    /// Time-based, with period in seconds
+    Totp(u32),
+    /// Counter-based, with initial counter
+    Hotp(u64),
+}
+
+/// OTP provisioning (`otpauth://` URI)
+#[derive(Debug, Clone)]
+pub struct Otp {
+    pub otp_type: OtpType,
+    pub issuer: Option<String>,
+    pub account: String,
+    /// Base32 secret without padding
+    pub secret: String,
+    /// Secret was generated randomly
+    pub generated_secret: bool,
+    pub algorithm: String,
+    pub digits: u32,
+}
+
+impl Otp {
+    /// Fields: `issuer`, `account`, `secret` (base32, `random` or not set to generate), `algorithm` (SHA1|SHA256|SHA512),
+    /// `digits` (6|7|8), `period` (totp, default: 30), `counter` (hotp, default: 0)
+    pub fn from_fields(fields: &Fields, hotp: bool) -> Result<Self, String> {
+        let otp_type = if hotp {
+            let counter = optional(fields, "counter").unwrap_or("0");
+            OtpType::Hotp(
+                counter
+                    .parse()
+                    .map_err(|_| format!("Invalid counter: \"{}\"", counter))?,
+            )
+        } else {
+            let period = optional(fields, "period").unwrap_or("30");
+            OtpType::Totp(
+                period
+                    .parse()
+                    .ok()
+                    .filter(|p| *p > 0)
+                    .ok_or(format!("Invalid period: \"{}\"", period))?,
+            )
+        };
+
+        // Empty secret (e.g. blank cell of data file) is an error, not a generated secret
+        let (secret, generated_secret) = match fields.get("secret").map(String::as_str) {
+            None | Some("random") => (random_secret()?, true),
+            Some(secret) => (normalize_secret(secret)?, false),
+        };
+
+        let algorithm = optional(fields, "algorithm")
+            .unwrap_or("SHA1")
+            .to_uppercase();
+        if !matches!(algorithm.as_str(), "SHA1" | "SHA256" | "SHA512") {
+            return Err(format!("Invalid algorithm: \"{}\"", algorithm));
+        }
+
+        let digits = optional(fields, "digits").unwrap_or("6");
+        let digits = digits
+            .parse()
+            .ok()
+            .filter(|d| (6..=8).contains(d))
+            .ok_or(format!("Invalid digits (6-8): \"{}\"", digits))?;
+
+        let issuer = optional(fields, "issuer").map(String::from);
+        if issuer.as_deref().is_some_and(|i| i.contains(':')) {
+            return Err("Issuer must not contain \":\"".to_string());
+        }
+
+        Ok(Self {
+            otp_type,
+            issuer,
+            account: required(fields, "account")?.to_string(),
+            secret,
+            generated_secret,
+            algorithm,
+            digits,
+        })
+    }
+
+    pub fn build(&self) -> String {
+        let (kind, parameter) = match self.otp_type {
+            OtpType::Totp(period) => ("totp", format!("period={}", period)),
+            OtpType::Hotp(counter) => ("hotp", format!("counter={}", counter)),
+        };
+
+        let label = match &self.issuer {
+            Some(issuer) => format!(
+                "{}:{}",
+                percent_encode(issuer),
+                percent_encode(&self.account)
+            ),
+            None => percent_encode(&self.account),
+        };
+
+        let mut output = format!("otpauth://{}/{}?secret={}", kind, label, self.secret);
+        if let Some(issuer) = &self.issuer {
+            output.push_str(&format!("&issuer={}", percent_encode(issuer)));
+        }
+        output.push_str(&format!(
+            "&algorithm={}&digits={}&{}",
+            self.algorithm, self.digits, parameter
+        ));
+        output
+    }
+}
+
+// Validate base32 secret, remove spaces and padding
+fn normalize_secret(value: &str) -> Result<String, String> {
+    let secret: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .collect::<String>()
+        .to_uppercase();
+    if secret.is_empty() || !secret.bytes().all(|b| BASE32_ALPHABET.contains(&b)) {
+        return Err(format!("Invalid base32 secret: \"{}\"", value));
+    }
+    Ok(secret)
+}
+
+// 160 bits secret (RFC 4226 recommended length)
+fn random_secret() -> Result<String, String> {
+    let mut bytes = [0u8; 20];
+    getrandom::fill(&mut bytes).map_err(|e| format!("Cannot generate secret: {}", e))?;
+    Ok(base32_encode(&bytes))
+}
+
+/// Base32 encode without padding (RFC 4648)
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base32_rfc4648() {
+        for (data, encoded) in [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ] {
+            assert_eq!(base32_encode(data.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn secret_normalized() {
+        assert_eq!(normalize_secret("mzxw 6ytb oi==").unwrap(), "MZXW6YTBOI");
+        assert!(normalize_secret("MZXW1").is_err());
+    }
+}
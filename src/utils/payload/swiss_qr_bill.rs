@@ -0,0 +1,207 @@
+use super::epc::{check_length, parse_amount, validate_creditor_reference, validate_iban};
+use super::{optional, required, Fields};
+
+/// Structured address (address type S)
+#[derive(Debug, Clone, Default)]
+pub struct Address {
+    pub name: String,
+    pub street: Option<String>,
+    pub building: Option<String>,
+    pub postcode: String,
+    pub city: String,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: String,
+}
+
+impl Address {
+    // Fields with prefix, e.g. `debtor_name`
+    fn from_fields(fields: &Fields, prefix: &str) -> Result<Self, String> {
+        let get = |key: &str| optional(fields, &format!("{}{}", prefix, key)).map(String::from);
+        let require = |key: &str| {
+            required(fields, &format!("{}{}", prefix, key)).map(|v| v.trim().to_string())
+        };
+
+        let address = Self {
+            name: require("name")?,
+            street: get("street"),
+            building: get("building"),
+            postcode: require("postcode")?,
+            city: require("city")?,
+            country: require("country")?.to_uppercase(),
+        };
+
+        check_length("name", &address.name, 70)?;
+        check_length("street", address.street.as_deref().unwrap_or_default(), 70)?;
+        check_length(
+            "building",
+            address.building.as_deref().unwrap_or_default(),
+            16,
+        )?;
+        check_length("postcode", &address.postcode, 16)?;
+        check_length("city", &address.city, 35)?;
+        if address.country.len() != 2 {
+            return Err(format!("Invalid country: \"{}\"", address.country));
+        }
+
+        Ok(address)
+    }
+
+    fn lines(&self) -> [String; 7] {
+        [
+            "S".to_string(),
+            self.name.clone(),
+            self.street.clone().unwrap_or_default(),
+            self.building.clone().unwrap_or_default(),
+            self.postcode.clone(),
+            self.city.clone(),
+            self.country.clone(),
+        ]
+    }
+}
+
+/// Reference type
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    /// QR reference (27 digits, with QR-IBAN)
+    Qrr(String),
+    /// Creditor reference (ISO 11649)
+    Scor(String),
+    Non,
+}
+
+/// Swiss QR-bill payment part (SPC version 0200)
+#[derive(Debug, Clone)]
+pub struct SwissQrBill {
+    pub iban: String,
+    pub creditor: Address,
+    pub amount: Option<String>,
+    /// CHF or EUR
+    pub currency: String,
+    pub debtor: Option<Address>,
+    pub reference: Reference,
+    pub message: Option<String>,
+    pub bill_information: Option<String>,
+}
+
+impl SwissQrBill {
+    /// Fields: `iban`, `name`, `street`, `building`, `postcode`, `city`, `country`, `amount`,
+    /// `currency` (default: CHF), `debtor_` prefixed address fields, `reference`, `message`, `bill_info`
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let iban = validate_iban(required(fields, "iban")?)?;
+        if !iban.starts_with("CH") && !iban.starts_with("LI") {
+            return Err(format!("IBAN must be CH or LI: \"{}\"", iban));
+        }
+
+        let currency = optional(fields, "currency").unwrap_or("CHF").to_uppercase();
+        if currency != "CHF" && currency != "EUR" {
+            return Err(format!("Currency must be CHF or EUR: \"{}\"", currency));
+        }
+
+        let debtor = match optional(fields, "debtor_name") {
+            Some(_) => Some(Address::from_fields(fields, "debtor_")?),
+            None => None,
+        };
+
+        // QR-IBAN has institution ID 30000-31999 and requires QR reference
+        let qr_iban = iban[4..9]
+            .parse::<u32>()
+            .map(|iid| (30000..=31999).contains(&iid))
+            .unwrap_or(false);
+        let reference = match optional(fields, "reference").map(|v| v.replace(' ', "")) {
+            Some(r) if qr_iban => {
+                validate_qr_reference(&r)?;
+                Reference::Qrr(r)
+            }
+            None if qr_iban => return Err("QR-IBAN requires QR reference".to_string()),
+            Some(r) => {
+                let r = r.to_uppercase();
+                validate_creditor_reference(&r)?;
+                Reference::Scor(r)
+            }
+            None => Reference::Non,
+        };
+
+        let message = optional(fields, "message").map(String::from);
+        let bill_information = optional(fields, "bill_info").map(String::from);
+        let additional = format!(
+            "{}{}",
+            message.as_deref().unwrap_or_default(),
+            bill_information.as_deref().unwrap_or_default()
+        );
+        check_length("message and bill_info", &additional, 140)?;
+
+        Ok(Self {
+            iban,
+            creditor: Address::from_fields(fields, "")?,
+            amount: optional(fields, "amount")
+                .map(|v| parse_amount(v, 999_999_999.99))
+                .transpose()?,
+            currency,
+            debtor,
+            reference,
+            message,
+            bill_information,
+        })
+    }
+
+    pub fn build(&self) -> Result<String, String> {
+        let mut lines: Vec<String> = vec![
+            "SPC".to_string(),
+            "0200".to_string(),
+            // Coding type UTF-8
+            "1".to_string(),
+            self.iban.clone(),
+        ];
+        lines.extend(self.creditor.lines());
+        // Ultimate creditor, reserved for future use
+        lines.extend(std::iter::repeat_n(String::new(), 7));
+        lines.push(self.amount.clone().unwrap_or_default());
+        lines.push(self.currency.clone());
+        match &self.debtor {
+            Some(debtor) => lines.extend(debtor.lines()),
+            None => lines.extend(std::iter::repeat_n(String::new(), 7)),
+        }
+        let (reference_type, reference) = match &self.reference {
+            Reference::Qrr(r) => ("QRR", r.clone()),
+            Reference::Scor(r) => ("SCOR", r.clone()),
+            Reference::Non => ("NON", String::new()),
+        };
+        lines.push(reference_type.to_string());
+        lines.push(reference);
+        lines.push(self.message.clone().unwrap_or_default());
+        lines.push("EPD".to_string());
+        if let Some(bill_information) = &self.bill_information {
+            lines.push(bill_information.clone());
+        }
+
+        let output = lines.join("\r\n");
+        if output.chars().count() > 997 {
+            return Err(format!(
+                "Swiss QR-bill payload is longer than 997 characters: {}",
+                output.chars().count()
+            ));
+        }
+        Ok(output)
+    }
+}
+
+/// Validate QR reference, 27 digits with modulo 10 recursive check digit
+pub fn validate_qr_reference(value: &str) -> Result<(), String> {
+    const TABLE: [u32; 10] = [0, 9, 4, 6, 8, 2, 7, 1, 3, 5];
+
+    let digits: Option<Vec<u32>> = value.chars().map(|c| c.to_digit(10)).collect();
+    let valid = match digits {
+        Some(digits) if digits.len() == 27 => {
+            let carry = digits[..26]
+                .iter()
+                .fold(0, |carry, d| TABLE[((carry + d) % 10) as usize]);
+            (10 - carry) % 10 == digits[26]
+        }
+        _ => false,
+    };
+
+    if !valid {
+        return Err(format!("Invalid QR reference: \"{}\"", value));
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use super::{optional, required, Fields};
+
+/// Percent-encode all characters except unreserved (RFC 3986)
+pub fn percent_encode(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            output.push(byte as char);
+        } else {
+            output.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    output
+}
+
+// Append query parameters that have value
+fn query(params: &[(&str, Option<&str>)]) -> String {
+    let params: Vec<String> = params
+        .iter()
+        .filter_map(|(key, value)| value.map(|v| format!("{}={}", key, percent_encode(v))))
+        .collect();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", params.join("&"))
+    }
+}
+
+// Phone number, keep only digits, leading `+` and visual separators
+fn phone_number(value: &str) -> Result<String, String> {
+    let number: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let valid = !number.is_empty()
+        && number.char_indices().all(|(i, c)| {
+            c.is_ascii_digit() || matches!(c, '-' | '.' | '(' | ')') || (c == '+' && i == 0)
+        });
+    if !valid {
+        return Err(format!("Invalid phone number: \"{}\"", value));
+    }
+    Ok(number)
+}
+
+fn coordinate(fields: &Fields, key: &str, limit: f64) -> Result<f64, String> {
+    let value = required(fields, key)?;
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.abs() <= limit)
+        .ok_or(format!("Invalid {}: \"{}\"", key, value))
+}
+
+/// `geo:` URI (RFC 5870), fields: `lat`, `lon`, `alt`
+pub fn geo(fields: &Fields) -> Result<String, String> {
+    let lat = coordinate(fields, "lat", 90.0)?;
+    let lon = coordinate(fields, "lon", 180.0)?;
+    let mut output = format!("geo:{},{}", lat, lon);
+    if let Some(alt) = optional(fields, "alt") {
+        let alt = alt
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid alt: \"{}\"", alt))?;
+        output.push_str(&format!(",{}", alt));
+    }
+    Ok(output)
+}
+
+/// `mailto:` URI (RFC 6068), fields: `to` (multiple separated by `,`), `cc`, `bcc`, `subject`, `body`
+pub fn mailto(fields: &Fields) -> Result<String, String> {
+    let to: Vec<String> = required(fields, "to")?
+        .split(',')
+        .map(|address| {
+            let address = address.trim();
+            if !address.contains('@') {
+                return Err(format!("Invalid email address: \"{}\"", address));
+            }
+            // Keep `@` readable
+            Ok(address
+                .split('@')
+                .map(percent_encode)
+                .collect::<Vec<String>>()
+                .join("@"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(format!(
+        "mailto:{}{}",
+        to.join(","),
+        query(&[
+            ("cc", optional(fields, "cc")),
+            ("bcc", optional(fields, "bcc")),
+            ("subject", optional(fields, "subject")),
+            ("body", optional(fields, "body")),
+        ])
+    ))
+}
+
+/// `sms:` URI (RFC 5724), fields: `phone`, `body`
+pub fn sms(fields: &Fields) -> Result<String, String> {
+    Ok(format!(
+        "sms:{}{}",
+        phone_number(required(fields, "phone")?)?,
+        query(&[("body", optional(fields, "body"))])
+    ))
+}
+
+/// `tel:` URI (RFC 3966), fields: `phone`
+pub fn tel(fields: &Fields) -> Result<String, String> {
+    Ok(format!("tel:{}", phone_number(required(fields, "phone")?)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(values: &[(&str, &str)]) -> Fields {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn mailto_percent_encoding() {
+        let uri = mailto(&fields(&[
+            ("to", "a+b@example.com, c@example.org"),
+            ("subject", "Hi & bye?"),
+            ("body", "Line 1\nZürich"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            uri,
+            "mailto:a%2Bb@example.com,c@example.org?subject=Hi%20%26%20bye%3F&body=Line%201%0AZ%C3%BCrich"
+        );
+        assert!(mailto(&fields(&[("to", "nobody")])).is_err());
+    }
+
+    #[test]
+    fn sms_and_tel() {
+        assert_eq!(
+            sms(&fields(&[
+                ("phone", "+1 (555) 010-9999"),
+                ("body", "50% off")
+            ]))
+            .unwrap(),
+            "sms:+1(555)010-9999?body=50%25%20off"
+        );
+        assert_eq!(
+            tel(&fields(&[("phone", "+44 20 7946 0000")])).unwrap(),
+            "tel:+442079460000"
+        );
+        assert!(tel(&fields(&[("phone", "12+3")])).is_err());
+        assert!(tel(&fields(&[("phone", "555;ext=1")])).is_err());
+    }
+
+    #[test]
+    fn geo_uri() {
+        assert_eq!(
+            geo(&fields(&[
+                ("lat", "48.2010"),
+                ("lon", "16.3695"),
+                ("alt", "183")
+            ]))
+            .unwrap(),
+            "geo:48.201,16.3695,183"
+        );
+        assert!(geo(&fields(&[("lat", "91"), ("lon", "0")])).is_err());
+        assert!(geo(&fields(&[("lat", "0"), ("lon", "-180.5")])).is_err());
+    }
+}
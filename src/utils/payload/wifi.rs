@@ -0,0 +1,120 @@
+use std::fmt;
+
+use super::{flag, optional, required, Fields};
+
+/// Wi-Fi authentication type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Auth {
+    NoPass,
+    Wep,
+    /// WPA/WPA2/WPA3 personal
+    Wpa,
+}
+
+/// Wi-Fi network config (`WIFI:` string)
+#[derive(Debug, Clone)]
+pub struct Wifi {
+    pub ssid: String,
+    pub password: Option<String>,
+    pub auth: Auth,
+    pub hidden: bool,
+}
+
+impl Wifi {
+    /// Fields: `ssid`, `password`, `auth` (nopass|wep|wpa, default: wpa or nopass without password), `hidden`
+    pub fn from_fields(fields: &Fields) -> Result<Self, String> {
+        let password = optional(fields, "password").map(String::from);
+
+        let auth = match optional(fields, "auth")
+            .map(|v| v.to_lowercase())
+            .as_deref()
+        {
+            None if password.is_none() => Auth::NoPass,
+            None | Some("wpa") | Some("wpa2") | Some("wpa3") => Auth::Wpa,
+            Some("wep") => Auth::Wep,
+            Some("nopass") | Some("none") => Auth::NoPass,
+            Some(v) => return Err(format!("Invalid Wi-Fi auth type: \"{}\"", v)),
+        };
+
+        if auth != Auth::NoPass && password.is_none() {
+            return Err("Wi-Fi password is required for auth type WEP/WPA".to_string());
+        }
+
+        Ok(Self {
+            ssid: required(fields, "ssid")?.to_string(),
+            password: if auth == Auth::NoPass { None } else { password },
+            auth,
+            hidden: flag(fields, "hidden")?,
+        })
+    }
+}
+
+// Escape special characters with backslash
+fn escape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
+
+impl fmt::Display for Wifi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let auth = match self.auth {
+            Auth::NoPass => "nopass",
+            Auth::Wep => "WEP",
+            Auth::Wpa => "WPA",
+        };
+        write!(f, "WIFI:T:{};S:{};", auth, escape(&self.ssid))?;
+        if let Some(password) = &self.password {
+            write!(f, "P:{};", escape(password))?;
+        }
+        if self.hidden {
+            write!(f, "H:true;")?;
+        }
+        write!(f, ";")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wifi(values: &[(&str, &str)]) -> Result<String, String> {
+        let fields = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Wifi::from_fields(&fields).map(|w| w.to_string())
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            wifi(&[("ssid", "My;Net,1"), ("password", r#"a:b\c""#)]).unwrap(),
+            r#"WIFI:T:WPA;S:My\;Net\,1;P:a\:b\\c\";;"#
+        );
+    }
+
+    #[test]
+    fn hidden_and_auth() {
+        assert_eq!(
+            wifi(&[("ssid", "Lab"), ("hidden", "yes")]).unwrap(),
+            "WIFI:T:nopass;S:Lab;H:true;;"
+        );
+        assert_eq!(
+            wifi(&[("ssid", "Lab"), ("password", "x"), ("auth", "WEP")]).unwrap(),
+            "WIFI:T:WEP;S:Lab;P:x;;"
+        );
+        // Password is dropped for open network
+        assert_eq!(
+            wifi(&[("ssid", "Lab"), ("password", "x"), ("auth", "nopass")]).unwrap(),
+            "WIFI:T:nopass;S:Lab;;"
+        );
+        assert!(wifi(&[("ssid", "Lab"), ("auth", "wpa")]).is_err());
+        assert!(wifi(&[("ssid", "Lab"), ("hidden", "maybe")]).is_err());
+    }
+}
@@ -1,16 +1,485 @@
+use calamine::{open_workbook_auto_from_rs, Data, Reader};
 use csv::Error as ErrorCsv;
+use serde_json::Value;
+use std::io::{Cursor, Read};
+use std::path::Path;
 
 // Get data from csv format file
-pub fn csv_to_vec(path: &String) -> Result<Vec<Vec<String>>, ErrorCsv> {
+#[deprecated(note = "use `read_table` with format `csv`")]
+pub fn csv_to_vec(path: &str) -> Result<Vec<Vec<String>>, ErrorCsv> {
+    let opt = ReadOptions {
+        format: Some("csv".to_string()),
+        ..Default::default()
+    };
+    read_table(path, &opt)
+        .map(|table| table.rows)
+        .map_err(|e| ErrorCsv::from(std::io::Error::other(e)))
+}
+
+/// Options for reading input file
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Input format (csv|tsv|json|jsonl|xlsx|ods), detect by file extension if not set
+    pub format: Option<String>,
+    /// Field delimiter of csv (default `,`, tsv is tab)
+    pub delimiter: Option<String>,
+    /// Quote character of csv (default `"`)
+    pub quote: Option<String>,
+    /// Text encoding label, e.g. `utf-8`, `windows-1252`, `shift_jis` (default utf-8 or by BOM)
+    pub encoding: Option<String>,
+    /// First row of csv or spreadsheet is header
+    pub header: bool,
+    /// Number of rows (csv records) to skip before header or data of csv or spreadsheet
+    pub skip: usize,
+    /// Spreadsheet sheet name or number (start at 1) (default first sheet)
+    pub sheet: Option<String>,
+    /// Spreadsheet cell range, e.g. `A2:D100` or `B3` to the end (default used cells)
+    pub range: Option<String>,
+}
+
+/// Rows of input file, with column names when the file has them
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Get input format from file extension
+pub fn detect_format(path: &str) -> String {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    match extension.as_str() {
+        "json" => "json",
+        "jsonl" | "ndjson" => "jsonl",
+        "tsv" | "tab" => "tsv",
+        "xlsx" | "xlsm" | "xlsb" | "xls" => "xlsx",
+        "ods" => "ods",
+        _ => "csv",
+    }
+    .to_string()
+}
+
+/// Read input file to table by format, path `-` reads from stdin
+pub fn read_table(path: &str, opt: &ReadOptions) -> Result<Table, String> {
+    let bytes = if path == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| format!("Cannot read stdin: {}", e))?;
+        bytes
+    } else {
+        std::fs::read(path).map_err(|e| format!("Cannot read file \"{}\": {}", path, e))?
+    };
+
+    let format = opt.format.clone().unwrap_or_else(|| detect_format(path));
+    if matches!(format.to_lowercase().as_str(), "xlsx" | "xls" | "ods") {
+        return spreadsheet_to_table(bytes, opt);
+    }
+
+    let text = decode(&bytes, opt.encoding.as_deref())?;
+    match format.to_lowercase().as_str() {
+        "csv" => delimited_to_table(&text, opt.delimiter.as_deref().unwrap_or(","), opt),
+        "tsv" => delimited_to_table(&text, opt.delimiter.as_deref().unwrap_or("\t"), opt),
+        "json" => {
+            let value: Value =
+                serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e))?;
+            match value {
+                Value::Array(items) => objects_to_table(items),
+                _ => Err("JSON input must be an array of objects".to_string()),
+            }
+        }
+        "jsonl" => {
+            let items = text
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(index, line)| {
+                    serde_json::from_str(line)
+                        .map_err(|e| format!("Invalid JSON on line {}: {}", index + 1, e))
+                })
+                .collect::<Result<Vec<Value>, String>>()?;
+            objects_to_table(items)
+        }
+        v => Err(format!(
+            "Unsupported input format: \"{}\" (csv|tsv|json|jsonl|xlsx|ods)",
+            v
+        )),
+    }
+}
+
+// Decode bytes to text, BOM takes priority over given encoding
+fn decode(bytes: &[u8], encoding: Option<&str>) -> Result<String, String> {
+    let encoding = match encoding {
+        Some(label) => encoding_rs::Encoding::for_label(label.as_bytes())
+            .ok_or(format!("Unknown encoding: \"{}\"", label))?,
+        None => encoding_rs::UTF_8,
+    };
+    let (text, _, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        return Err(format!("File is not valid {} text", encoding.name()));
+    }
+    Ok(text.into_owned())
+}
+
+// Get single byte character, `\t` or `tab` is tab
+fn single_byte(value: &str, name: &str) -> Result<u8, String> {
+    match value {
+        "\\t" | "tab" => Ok(b'\t'),
+        v if v.len() == 1 => Ok(v.as_bytes()[0]),
+        v => Err(format!(
+            "{} must be a single ASCII character: \"{}\"",
+            name, v
+        )),
+    }
+}
+
+fn delimited_to_table(text: &str, delimiter: &str, opt: &ReadOptions) -> Result<Table, String> {
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(false)
-        .from_path(path)?;
+        .delimiter(single_byte(delimiter, "Delimiter")?)
+        .quote(single_byte(opt.quote.as_deref().unwrap_or("\""), "Quote")?)
+        // Skipped records (e.g. title above header) may have any number of fields
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for record in reader.records().skip(opt.skip) {
+        let record = record.map_err(|e| e.to_string())?;
+        if let Some(first) = rows.first().filter(|first| first.len() != record.len()) {
+            return Err(format!(
+                "CSV error: record {} (line {}): found record with {} fields, but the previous record has {} fields",
+                record.position().map_or(0, |p| p.record()),
+                record.position().map_or(0, |p| p.line()),
+                record.len(),
+                first.len()
+            ));
+        }
+        rows.push(record.iter().map(String::from).collect());
+    }
+
+    Ok(take_header(rows, opt))
+}
+
+// Use first row as header when set
+fn take_header(mut rows: Vec<Vec<String>>, opt: &ReadOptions) -> Table {
+    let headers = if opt.header && !rows.is_empty() {
+        rows.remove(0)
+            .iter()
+            .map(|name| name.trim().to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Table { headers, rows }
+}
+
+// Read sheet of xlsx, xls, xlsb or ods, text is kept as is (no encoding needed)
+fn spreadsheet_to_table(bytes: Vec<u8>, opt: &ReadOptions) -> Result<Table, String> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
+        .map_err(|e| format!("Cannot open spreadsheet: {}", e))?;
+    let sheet_names = workbook.sheet_names();
+
+    let sheet_name = match opt.sheet.as_deref() {
+        None => sheet_names.first().cloned(),
+        Some(name) => match name.parse::<usize>() {
+            Ok(number) if number >= 1 => sheet_names.get(number - 1).cloned(),
+            _ => sheet_names.iter().find(|n| n.as_str() == name).cloned(),
+        },
+    }
+    .ok_or(format!(
+        "Sheet \"{}\" not found, available: {}",
+        opt.sheet.clone().unwrap_or_default(),
+        sheet_names.join(", ")
+    ))?;
+
+    let mut range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|e| format!("Cannot read sheet \"{}\": {}", sheet_name, e))?;
+
+    if let Some(cells) = &opt.range {
+        let (start, end) = match cells.split_once(':') {
+            Some((start, end)) => (parse_cell(start)?, Some(parse_cell(end)?)),
+            None => (parse_cell(cells)?, None),
+        };
+        let end = end.unwrap_or(range.end().unwrap_or(start));
+        if end.0 < start.0 || end.1 < start.1 {
+            return Err(format!("Invalid cell range: \"{}\"", cells));
+        }
+        range = range.range(start, end);
+    }
+
+    let rows = range
+        .rows()
+        .skip(opt.skip)
+        .map(|row| row.iter().map(cell_to_string).collect())
+        .collect();
+
+    Ok(take_header(rows, opt))
+}
+
+// Parse cell reference like `B12` to (row, column) from 0
+fn parse_cell(cell: &str) -> Result<(u32, u32), String> {
+    let cell = cell.trim().to_uppercase();
+    let split = cell
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(cell.len());
+    let (letters, digits) = cell.split_at(split);
+
+    // Too long column is an error, not an overflow
+    let column = letters.chars().try_fold(0u32, |column, c| match c {
+        'A'..='Z' => column
+            .checked_mul(26)
+            .and_then(|column| column.checked_add(c as u32 - 'A' as u32 + 1)),
+        _ => None,
+    });
+    match (column, digits.parse::<u32>()) {
+        (Some(column), Ok(row)) if column >= 1 && row >= 1 => Ok((row - 1, column - 1)),
+        _ => Err(format!("Invalid cell reference: \"{}\"", cell)),
+    }
+}
+
+// Cell value as text, date is written as `YYYY-MM-DD` (with time if any)
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(date) if date.is_datetime() => {
+            let (year, month, day, hour, minute, second, _) = date.to_ymd_hms_milli();
+            if (hour, minute, second) == (0, 0, 0) {
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            } else {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    year, month, day, hour, minute, second
+                )
+            }
+        }
+        v => v.to_string(),
+    }
+}
+
+// Columns are keys in order of first appearance, missing keys are empty
+fn objects_to_table(items: Vec<Value>) -> Result<Table, String> {
+    let mut headers: Vec<String> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let object = item
+            .as_object()
+            .ok_or(format!("Item {} is not an object", index + 1))?;
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+
+    let rows = items
+        .iter()
+        .map(|item| {
+            headers
+                .iter()
+                .map(|key| match item.get(key) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(v)) => v.clone(),
+                    Some(v) => v.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(Table { headers, rows })
+}
+
+/// Run query on SQLite database, column names of result are headers
+#[cfg(feature = "sqlite")]
+pub fn sqlite_to_table(path: &str, query: &str) -> Result<Table, String> {
+    use rusqlite::{types::ValueRef, Connection, OpenFlags};
 
-    let mut csv_data: Vec<Vec<String>> = Vec::new();
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Cannot open database \"{}\": {}", path, e))?;
+    let mut statement = connection
+        .prepare(query)
+        .map_err(|e| format!("Invalid query: {}", e))?;
 
-    for record in reader.records() {
-        csv_data.push(record.unwrap().iter().map(String::from).collect());
+    let headers: Vec<String> = statement
+        .column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut result = statement.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = result.next().map_err(|e| e.to_string())? {
+        let values = (0..headers.len())
+            .map(|index| match row.get_ref(index) {
+                Ok(ValueRef::Null) | Err(_) => String::new(),
+                Ok(ValueRef::Integer(v)) => v.to_string(),
+                Ok(ValueRef::Real(v)) => v.to_string(),
+                Ok(ValueRef::Text(v)) => String::from_utf8_lossy(v).to_string(),
+                Ok(ValueRef::Blob(v)) => v.iter().map(|b| format!("{:02x}", b)).collect(),
+            })
+            .collect();
+        rows.push(values);
     }
 
-    Ok(csv_data)
+    Ok(Table { headers, rows })
+}
+
+#[cfg(not(feature = "sqlite"))]
+pub fn sqlite_to_table(_path: &str, _query: &str) -> Result<Table, String> {
+    Err("SQLite input is not available in this build (feature \"sqlite\")".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+    use serde_json::json;
+
+    #[test]
+    fn read_json_and_jsonl() {
+        let dir = TempDir::new("read-json");
+        let json = dir.file(
+            "items.json",
+            br#"[{"id": 1, "name": "Ann"}, {"id": 2, "tags": ["a"], "name": null}]"#,
+        );
+        let table = read_table(&json, &ReadOptions::default()).unwrap();
+        assert_eq!(table.headers, ["id", "name", "tags"]);
+        assert_eq!(table.rows, [["1", "Ann", ""], ["2", "", "[\"a\"]"]]);
+
+        let jsonl = dir.file("items.jsonl", b"{\"id\": 1}\n\n{\"id\": \"2\"}\n");
+        let table = read_table(&jsonl, &ReadOptions::default()).unwrap();
+        assert_eq!(table.rows, [["1"], ["2"]]);
+
+        let invalid = dir.file("invalid.jsonl", b"{\"id\": 1}\n{id}\n");
+        let error = read_table(&invalid, &ReadOptions::default()).unwrap_err();
+        assert!(error.starts_with("Invalid JSON on line 2"), "{}", error);
+
+        let object = dir.file("object.json", br#"{"id": 1}"#);
+        assert!(read_table(&object, &ReadOptions::default()).is_err());
+    }
+
+    #[test]
+    fn read_tsv_and_delimiter() {
+        let dir = TempDir::new("read-tsv");
+        let tsv = dir.file("data.tsv", b"name\tcity\nAnn\tOslo, NO\n");
+        let opt = ReadOptions {
+            header: true,
+            ..Default::default()
+        };
+        let table = read_table(&tsv, &opt).unwrap();
+        assert_eq!(table.headers, ["name", "city"]);
+        assert_eq!(table.rows, [["Ann", "Oslo, NO"]]);
+
+        let csv = dir.file("data.txt", b"a;'b;c'\n");
+        let opt = ReadOptions {
+            delimiter: Some(";".to_string()),
+            quote: Some("'".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(read_table(&csv, &opt).unwrap().rows, [["a", "b;c"]]);
+        let opt = ReadOptions {
+            delimiter: Some(";;".to_string()),
+            ..Default::default()
+        };
+        assert!(read_table(&csv, &opt).is_err());
+    }
+
+    #[test]
+    fn decode_encoding() {
+        assert_eq!(decode(b"\xef\xbb\xbfname", None).unwrap(), "name");
+        assert_eq!(decode(b"caf\xe9", Some("windows-1252")).unwrap(), "café");
+        assert_eq!(
+            decode(b"\x83e\x83X\x83g", Some("shift_jis")).unwrap(),
+            "テスト"
+        );
+        // BOM takes priority over given encoding
+        assert_eq!(
+            decode(b"\xef\xbb\xbfcaf\xc3\xa9", Some("windows-1252")).unwrap(),
+            "café"
+        );
+        assert!(decode(b"caf\xe9", None).is_err());
+        assert!(decode(b"name", Some("klingon")).is_err());
+    }
+
+    #[test]
+    fn objects_to_table_columns() {
+        let table = objects_to_table(vec![
+            json!({"b": true, "a": 1.5}),
+            json!({"c": {"x": 1}, "a": "text"}),
+        ])
+        .unwrap();
+        assert_eq!(table.headers, ["b", "a", "c"]);
+        assert_eq!(table.rows, [["true", "1.5", ""], ["", "text", "{\"x\":1}"]]);
+        assert_eq!(
+            objects_to_table(vec![json!({"a": 1}), json!(2)]).unwrap_err(),
+            "Item 2 is not an object"
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn csv_to_vec_rows() {
+        let dir = TempDir::new("csv-to-vec");
+        let path = dir.file("data.csv", b"a,b\nc,d\n");
+        assert_eq!(csv_to_vec(&path).unwrap(), [["a", "b"], ["c", "d"]]);
+    }
+
+    #[test]
+    fn skip_records_with_quoted_newline() {
+        let text = "\"Report\nof May\"\nname,note\nalice,\"multi\nline\"\nbob,plain\n";
+        let opt = ReadOptions {
+            header: true,
+            skip: 1,
+            ..Default::default()
+        };
+        let table = delimited_to_table(text, ",", &opt).unwrap();
+        assert_eq!(table.headers, ["name", "note"]);
+        assert_eq!(table.rows, [["alice", "multi\nline"], ["bob", "plain"]]);
+    }
+
+    #[test]
+    fn uneven_records_after_skip() {
+        let opt = ReadOptions {
+            skip: 1,
+            ..Default::default()
+        };
+        assert!(delimited_to_table("title\na,b\nc,d\n", ",", &opt).is_ok());
+        assert!(delimited_to_table("title\na,b\nc\n", ",", &opt).is_err());
+    }
+
+    #[test]
+    fn cell_reference() {
+        assert_eq!(parse_cell("a1").unwrap(), (0, 0));
+        assert_eq!(parse_cell("AB12").unwrap(), (11, 27));
+        assert!(parse_cell("AAAAAAAAAA1").is_err());
+        assert!(parse_cell("A99999999999").is_err());
+        assert!(parse_cell("A0").is_err());
+        assert!(parse_cell("12").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "sqlite")]
+    fn sqlite_query() {
+        let dir = TempDir::new("sqlite");
+        let path = dir.file("data.db", b"");
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE item (id INTEGER, name TEXT, price REAL, code BLOB);
+                 INSERT INTO item VALUES (1, 'Ann', 2.5, x'00ff10'), (2, NULL, NULL, NULL);",
+            )
+            .unwrap();
+        drop(connection);
+
+        let table =
+            sqlite_to_table(&path, "SELECT id, name AS title, price, code FROM item").unwrap();
+        assert_eq!(table.headers, ["id", "title", "price", "code"]);
+        assert_eq!(
+            table.rows,
+            [["1", "Ann", "2.5", "00ff10"], ["2", "", "", ""]]
+        );
+
+        assert!(sqlite_to_table(&path, "SELECT * FROM missing").is_err());
+    }
 }
@@ -0,0 +1,171 @@
+use qrcode_generator::QrCodeEcc;
+use qrcodegen::{BitBuffer, QrCode, Version};
+
+// Error correction codewords per block, by error correction level and version (ISO/IEC 18004 table 9)
+static ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28,
+        30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+    [
+        0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30,
+        30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+];
+
+// Number of error correction blocks, by error correction level and version
+static NUM_ERROR_CORRECTION_BLOCKS: [[u8; 41]; 4] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13,
+        14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21,
+        23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+    [
+        0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29,
+        34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68,
+    ],
+    [
+        0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32,
+        35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81,
+    ],
+];
+
+// Number of data codewords of version and error correction level
+fn num_data_codewords(version: usize, ecc: QrCodeEcc) -> usize {
+    let mut raw_modules = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        raw_modules -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            raw_modules -= 36;
+        }
+    }
+    let level = match ecc {
+        QrCodeEcc::Low => 0,
+        QrCodeEcc::Medium => 1,
+        QrCodeEcc::Quartile => 2,
+        QrCodeEcc::High => 3,
+    };
+    raw_modules / 8
+        - ECC_CODEWORDS_PER_BLOCK[level][version] as usize
+            * NUM_ERROR_CORRECTION_BLOCKS[level][version] as usize
+}
+
+/// Error correction level from `l`, `m`, `q` or `h` (default medium)
+pub fn parse_ecc(level: &str) -> QrCodeEcc {
+    match level {
+        "l" => QrCodeEcc::Low,
+        "m" => QrCodeEcc::Medium,
+        "q" => QrCodeEcc::Quartile,
+        "h" => QrCodeEcc::High,
+        _ => QrCodeEcc::Medium,
+    }
+}
+
+/// QR version (1-40) for content, None when content is too long
+pub fn version(content: &str, ecc: QrCodeEcc) -> Option<u8> {
+    qrcode_generator::to_matrix(content, ecc)
+        .ok()
+        .map(|matrix| ((matrix.len() - 17) / 4) as u8)
+}
+
+/// Data codewords of QR in FNC1 first position mode (GS1 QR Code) with version,
+/// content is in byte mode, GS (0x1D) in content is FNC1 separator
+pub fn gs1_codewords(content: &str, ecc: QrCodeEcc) -> Result<(u8, Vec<u8>), String> {
+    let data = content.as_bytes();
+
+    // FNC1 indicator, byte mode indicator, character count and data
+    let count_bits = |version: usize| if version <= 9 { 8 } else { 16 };
+    let data_bits = |version: usize| 4 + 4 + count_bits(version) + data.len() * 8;
+
+    let version = (1..=40)
+        .find(|v| data_bits(*v) <= num_data_codewords(*v, ecc) * 8)
+        .ok_or("Content is too long for QR code")?;
+    let capacity = num_data_codewords(version, ecc) * 8;
+
+    let mut buffer = BitBuffer(Vec::new());
+    buffer.append_bits(0b0101, 4);
+    buffer.append_bits(0b0100, 4);
+    buffer.append_bits(data.len() as u32, count_bits(version) as u8);
+    for byte in data {
+        buffer.append_bits(*byte as u32, 8);
+    }
+
+    // Terminator, bit padding and pad codewords
+    buffer.append_bits(0, (capacity - buffer.0.len()).min(4) as u8);
+    buffer.append_bits(0, ((8 - buffer.0.len() % 8) % 8) as u8);
+    for pad in [0xec, 0x11].iter().cycle() {
+        if buffer.0.len() >= capacity {
+            break;
+        }
+        buffer.append_bits(*pad, 8);
+    }
+
+    let codewords: Vec<u8> = buffer
+        .0
+        .chunks(8)
+        .map(|bits| bits.iter().fold(0, |byte, bit| (byte << 1) | *bit as u8))
+        .collect();
+    Ok((version as u8, codewords))
+}
+
+/// Encode QR in FNC1 first position mode (GS1 QR Code), GS (0x1D) in content is FNC1 separator.
+/// Return modules as rows (`matrix[y][x]`), same as `qrcode_generator::to_matrix`
+pub fn to_matrix_gs1(content: &str, ecc: QrCodeEcc) -> Result<Vec<Vec<bool>>, String> {
+    let (version, codewords) = gs1_codewords(content, ecc)?;
+    let qr = QrCode::encode_codewords(Version::new(version), ecc, &codewords, None);
+    Ok((0..qr.size())
+        .map(|y| (0..qr.size()).map(|x| qr.get_module(x, y)).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ELEMENT_STRING: &str = "010950110153000310AB-123\x1d21SERIAL42";
+
+    #[test]
+    fn gs1_bit_stream() {
+        let (version, codewords) = gs1_codewords(ELEMENT_STRING, QrCodeEcc::Medium).unwrap();
+        let data = ELEMENT_STRING.as_bytes();
+        assert_eq!(version, 3);
+        assert_eq!(codewords.len(), num_data_codewords(3, QrCodeEcc::Medium));
+        // FNC1 first position (0101), byte mode (0100), 8 bits count, data
+        assert_eq!(codewords[0], 0b0101_0100);
+        assert_eq!(codewords[1] as usize, data.len());
+        assert_eq!(&codewords[2..2 + data.len()], data);
+        // Terminator and bit padding, then pad codewords
+        assert_eq!(codewords[2 + data.len()], 0);
+        assert_eq!(codewords[3 + data.len()..5 + data.len()], [0xec, 0x11]);
+    }
+
+    #[test]
+    fn gs1_count_bits_from_version_10() {
+        let content = "10".repeat(150);
+        let (version, codewords) = gs1_codewords(&content, QrCodeEcc::Medium).unwrap();
+        assert!(version >= 10);
+        // 16 bits count after the two mode indicators
+        assert_eq!(codewords[0], 0b0101_0100);
+        assert_eq!(u16::from_be_bytes([codewords[1], codewords[2]]), 300);
+        assert_eq!(codewords[3], b'1');
+    }
+
+    #[test]
+    fn gs1_matrix_size() {
+        let matrix = to_matrix_gs1(ELEMENT_STRING, QrCodeEcc::Medium).unwrap();
+        assert_eq!(matrix.len(), 29);
+        assert!(matrix.iter().all(|row| row.len() == 29));
+    }
+}
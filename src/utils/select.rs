@@ -0,0 +1,229 @@
+use std::cmp::Ordering;
+
+/// Options to process only some rows of data
+#[derive(Debug, Clone, Default)]
+pub struct RowSelection {
+    /// Row numbers (start at 1), e.g. `10-50`, `1,3,7-9` or `100-`
+    pub rows: Option<String>,
+    /// Filter expressions, all must match, e.g. `status == damaged`, `{{3}} >= 10`
+    pub filters: Vec<String>,
+    /// Take only first N rows after select and filter
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Contains,
+    GreaterEqual,
+    LessEqual,
+    Greater,
+    Less,
+}
+
+// Longer operators first, so `>=` is not taken as `>`
+static OPERATORS: [(&str, Operator); 8] = [
+    ("==", Operator::Equal),
+    ("!=", Operator::NotEqual),
+    ("~=", Operator::Contains),
+    (">=", Operator::GreaterEqual),
+    ("<=", Operator::LessEqual),
+    (">", Operator::Greater),
+    ("<", Operator::Less),
+    ("=", Operator::Equal),
+];
+
+struct Filter {
+    column: usize,
+    operator: &'static Operator,
+    value: String,
+}
+
+impl Filter {
+    fn parse(expression: &str, headers: &[String], count_col: usize) -> Result<Self, String> {
+        let (position, symbol, operator) = (0..expression.len())
+            .filter(|i| expression.is_char_boundary(*i))
+            .find_map(|i| {
+                OPERATORS
+                    .iter()
+                    .find(|(symbol, _)| expression[i..].starts_with(symbol))
+                    .map(|(symbol, operator)| (i, *symbol, operator))
+            })
+            .ok_or(format!(
+                "Invalid filter: \"{}\" (column == value, !=, ~=, >, >=, <, <=)",
+                expression
+            ))?;
+
+        let name = expression[..position].trim();
+        let name = name
+            .strip_prefix("{{")
+            .and_then(|n| n.strip_suffix("}}"))
+            .unwrap_or(name)
+            .trim();
+        let column = match name.parse::<usize>() {
+            Ok(number) if number >= 1 && number <= count_col => number - 1,
+            _ => headers
+                .iter()
+                .position(|h| h == name)
+                .ok_or(format!("Column \"{}\" in filter does not exist", name))?,
+        };
+
+        let value = expression[position + symbol.len()..].trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+
+        Ok(Filter {
+            column,
+            operator,
+            value: value.to_string(),
+        })
+    }
+
+    fn is_match(&self, row: &[String]) -> bool {
+        let cell = row.get(self.column).map(|c| c.trim()).unwrap_or_default();
+
+        // Compare as number when both are numbers
+        let ordering = match (cell.parse::<f64>(), self.value.parse::<f64>()) {
+            (Ok(a), Ok(b)) => a.partial_cmp(&b),
+            _ => Some(cell.cmp(self.value.as_str())),
+        };
+
+        match self.operator {
+            Operator::Equal => ordering == Some(Ordering::Equal),
+            Operator::NotEqual => ordering != Some(Ordering::Equal),
+            Operator::Contains => cell.contains(&self.value),
+            Operator::GreaterEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            Operator::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Operator::Greater => ordering == Some(Ordering::Greater),
+            Operator::Less => ordering == Some(Ordering::Less),
+        }
+    }
+}
+
+// Parse ranges of row numbers to list of (start, end), end is inclusive
+fn parse_ranges(ranges: &str) -> Result<Vec<(usize, usize)>, String> {
+    ranges
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            let number = |v: &str, default: usize| -> Result<usize, String> {
+                match v.trim() {
+                    "" => Ok(default),
+                    v => v
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|n| *n >= 1)
+                        .ok_or(format!("Invalid row range: \"{}\"", part)),
+                }
+            };
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (number(start, 1)?, number(end, usize::MAX)?),
+                None => (number(part, 0)?, number(part, 0)?),
+            };
+            if start == 0 || start > end {
+                return Err(format!("Invalid row range: \"{}\"", part));
+            }
+            Ok((start, end))
+        })
+        .collect()
+}
+
+/// Get index of rows to process, in order of file
+pub fn select_rows(
+    rows: &[Vec<String>],
+    headers: &[String],
+    selection: &RowSelection,
+) -> Result<Vec<usize>, String> {
+    let ranges = match &selection.rows {
+        Some(r) => Some(parse_ranges(r)?),
+        None => None,
+    };
+
+    let count_col = rows.first().map_or(0, |row| row.len()).max(headers.len());
+    let filters = selection
+        .filters
+        .iter()
+        .map(|f| Filter::parse(f, headers, count_col))
+        .collect::<Result<Vec<Filter>, String>>()?;
+
+    Ok((0..rows.len())
+        .filter(|index| {
+            ranges.as_ref().is_none_or(|ranges| {
+                ranges
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&(index + 1)))
+            })
+        })
+        .filter(|index| filters.iter().all(|f| f.is_match(&rows[*index])))
+        .take(selection.limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(values: &[[&str; 2]]) -> Vec<Vec<String>> {
+        values
+            .iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect()
+    }
+
+    fn headers() -> Vec<String> {
+        vec!["name".to_string(), "qty".to_string()]
+    }
+
+    #[test]
+    fn row_ranges() {
+        assert_eq!(parse_ranges("10-50").unwrap(), [(10, 50)]);
+        assert_eq!(parse_ranges("100-").unwrap(), [(100, usize::MAX)]);
+        assert_eq!(parse_ranges("1, 3,7-9").unwrap(), [(1, 1), (3, 3), (7, 9)]);
+        assert_eq!(parse_ranges("-3").unwrap(), [(1, 3)]);
+        for invalid in ["0", "5-3", "0-2", "a", "1,,2", "1-2-3"] {
+            assert!(parse_ranges(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn filter_number_and_text() {
+        let headers = headers();
+        let is_match = |expression: &str, cell: &str| {
+            Filter::parse(expression, &headers, 2)
+                .unwrap()
+                .is_match(&["x".to_string(), cell.to_string()])
+        };
+        // Numbers compare by value, not as text
+        assert!(is_match("qty > 9", "10"));
+        assert!(is_match("qty == 10", "10.0"));
+        assert!(is_match("{{2}} <= 2.5", " 2 "));
+        // Text compares by characters
+        assert!(is_match("qty < b", "abc"));
+        assert!(!is_match("qty > 9", "10a"));
+        assert!(is_match("qty ~= 0a", "10a"));
+        assert!(is_match("qty != 'abc'", "ab"));
+        assert!(Filter::parse("missing == 1", &headers, 2).is_err());
+        assert!(Filter::parse("qty", &headers, 2).is_err());
+    }
+
+    #[test]
+    fn limit_after_range_and_filter() {
+        let rows = table(&[["a", "1"], ["b", "5"], ["c", "7"], ["d", "9"], ["e", "8"]]);
+        let selection = RowSelection {
+            rows: Some("2-".to_string()),
+            filters: vec!["qty >= 7".to_string()],
+            limit: Some(2),
+        };
+        assert_eq!(select_rows(&rows, &headers(), &selection).unwrap(), [2, 3]);
+        let selection = RowSelection {
+            limit: Some(0),
+            ..Default::default()
+        };
+        assert!(select_rows(&rows, &headers(), &selection)
+            .unwrap()
+            .is_empty());
+    }
+}
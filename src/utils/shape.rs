@@ -0,0 +1,379 @@
+use rust_text_draw::image::{DynamicImage, Rgba};
+use rust_text_draw::{GenericImage, GenericImageView};
+
+use super::generate::get_alpha_color;
+
+/// Vector shape drawn in template percentage units
+/// (x and width of image width, y and height of image height).
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum Shape {
+    /// Rectangle with optional rounded corners
+    Rect {
+        #[serde(default)]
+        x: f32,
+        #[serde(default)]
+        y: f32,
+        #[serde(default = "full")]
+        w: f32,
+        #[serde(default = "full")]
+        h: f32,
+        /// Corner radius (percentage of image width)
+        #[serde(default)]
+        r: f32,
+        #[serde(flatten)]
+        paint: Paint,
+    },
+    /// Ellipse fitted in the given box
+    Ellipse {
+        #[serde(default)]
+        x: f32,
+        #[serde(default)]
+        y: f32,
+        #[serde(default = "full")]
+        w: f32,
+        #[serde(default = "full")]
+        h: f32,
+        #[serde(flatten)]
+        paint: Paint,
+    },
+    /// Circle by center, radius is percentage of image width
+    Circle {
+        cx: f32,
+        cy: f32,
+        r: f32,
+        #[serde(flatten)]
+        paint: Paint,
+    },
+    /// Straight line
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        #[serde(flatten)]
+        paint: Paint,
+    },
+    /// Connected line segments, can be closed and filled
+    Polyline {
+        pts: Vec<[f32; 2]>,
+        #[serde(default)]
+        closed: bool,
+        #[serde(flatten)]
+        paint: Paint,
+    },
+}
+
+/// Fill and stroke style of a shape
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct Paint {
+    /// Fill color (hex rrggbb or rrggbbaa)
+    pub fill: Option<String>,
+    /// Stroke color (hex rrggbb or rrggbbaa)
+    pub stroke: Option<String>,
+    /// Stroke width (percentage of image width)
+    pub sw: Option<f32>,
+}
+
+fn full() -> f32 {
+    100.0
+}
+
+/// Parse hex color `rrggbb` or `rrggbbaa` to rgba bytes
+pub fn parse_color(color: &str) -> Result<[u8; 4], String> {
+    let hex = color.trim_start_matches('#');
+    let value = u32::from_str_radix(hex, 16).map_err(|_| format!("Invalid color: \"{}\"", color));
+    match hex.len() {
+        6 => Ok((value? << 8 | 0xff).to_be_bytes()),
+        8 => Ok(value?.to_be_bytes()),
+        _ => Err(format!("Invalid color: \"{}\"", color)),
+    }
+}
+
+struct Canvas {
+    width: f32,
+    height: f32,
+}
+
+impl Canvas {
+    fn x(&self, percent: f32) -> f32 {
+        percent * self.width / 100.0
+    }
+
+    fn y(&self, percent: f32) -> f32 {
+        percent * self.height / 100.0
+    }
+}
+
+/// Draw a shape on image, return number of pixels that fall out of image
+pub fn draw_shape(image: &mut DynamicImage, shape: &Shape) -> Result<u32, String> {
+    let canvas = Canvas {
+        width: image.width() as f32,
+        height: image.height() as f32,
+    };
+
+    match shape {
+        Shape::Rect {
+            x,
+            y,
+            w,
+            h,
+            r,
+            paint,
+        } => {
+            let (x0, y0) = (canvas.x(*x), canvas.y(*y));
+            let (x1, y1) = (canvas.x(x + w), canvas.y(y + h));
+            let half = ((x1 - x0) / 2.0, (y1 - y0) / 2.0);
+            let center = (x0 + half.0, y0 + half.1);
+            let radius = canvas.x(*r).min(half.0).min(half.1).max(0.0);
+            paint_sdf(image, &canvas, paint, (x0, y0, x1, y1), |px, py| {
+                let qx = (px - center.0).abs() - half.0 + radius;
+                let qy = (py - center.1).abs() - half.1 + radius;
+                let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+                outside + qx.max(qy).min(0.0) - radius
+            })
+        }
+        Shape::Ellipse { x, y, w, h, paint } => {
+            let (x0, y0) = (canvas.x(*x), canvas.y(*y));
+            let (x1, y1) = (canvas.x(x + w), canvas.y(y + h));
+            let radius = ((x1 - x0) / 2.0, (y1 - y0) / 2.0);
+            let center = (x0 + radius.0, y0 + radius.1);
+            paint_sdf(image, &canvas, paint, (x0, y0, x1, y1), |px, py| {
+                ellipse_distance(px - center.0, py - center.1, radius.0, radius.1)
+            })
+        }
+        Shape::Circle { cx, cy, r, paint } => {
+            let center = (canvas.x(*cx), canvas.y(*cy));
+            let radius = canvas.x(*r);
+            let bounds = (
+                center.0 - radius,
+                center.1 - radius,
+                center.0 + radius,
+                center.1 + radius,
+            );
+            paint_sdf(image, &canvas, paint, bounds, |px, py| {
+                ((px - center.0).powi(2) + (py - center.1).powi(2)).sqrt() - radius
+            })
+        }
+        Shape::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            paint,
+        } => {
+            let a = (canvas.x(*x1), canvas.y(*y1));
+            let b = (canvas.x(*x2), canvas.y(*y2));
+            // Line has no fill, stroke with black by default
+            let paint = Paint {
+                fill: None,
+                stroke: Some(paint.stroke.clone().unwrap_or("000000".to_string())),
+                sw: paint.sw,
+            };
+            let bounds = (a.0.min(b.0), a.1.min(b.1), a.0.max(b.0), a.1.max(b.1));
+            paint_sdf(image, &canvas, &paint, bounds, |px, py| {
+                segment_distance((px, py), a, b)
+            })
+        }
+        Shape::Polyline { pts, closed, paint } => {
+            let points: Vec<(f32, f32)> = pts
+                .iter()
+                .map(|p| (canvas.x(p[0]), canvas.y(p[1])))
+                .collect();
+            if points.len() < 2 {
+                return Err("Polyline requires at least 2 points".to_string());
+            }
+
+            let mut segments: Vec<((f32, f32), (f32, f32))> =
+                points.windows(2).map(|p| (p[0], p[1])).collect();
+            if *closed {
+                segments.push((points[points.len() - 1], points[0]));
+            }
+
+            // Only closed polyline can be filled
+            let paint = Paint {
+                fill: if *closed { paint.fill.clone() } else { None },
+                ..paint.clone()
+            };
+
+            let bounds = points.iter().fold(
+                (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+                |(x0, y0, x1, y1), p| (x0.min(p.0), y0.min(p.1), x1.max(p.0), y1.max(p.1)),
+            );
+
+            paint_sdf(image, &canvas, &paint, bounds, |px, py| {
+                let distance = segments
+                    .iter()
+                    .map(|(a, b)| segment_distance((px, py), *a, *b))
+                    .fold(f32::MAX, f32::min);
+                if *closed && point_in_polygon((px, py), &points) {
+                    -distance
+                } else {
+                    distance
+                }
+            })
+        }
+    }
+}
+
+// Fill and stroke pixels from signed distance (negative is inside shape)
+fn paint_sdf<F>(
+    image: &mut DynamicImage,
+    canvas: &Canvas,
+    paint: &Paint,
+    bounds: (f32, f32, f32, f32),
+    distance: F,
+) -> Result<u32, String>
+where
+    F: Fn(f32, f32) -> f32,
+{
+    let fill = paint.fill.as_deref().map(parse_color).transpose()?;
+    let stroke = match (&paint.stroke, paint.sw) {
+        (None, None) => None,
+        (color, _) => Some(parse_color(color.as_deref().unwrap_or("000000"))?),
+    };
+    let half_stroke = canvas.x(paint.sw.unwrap_or(0.1)).max(0.5) / 2.0;
+
+    // Expand area to cover stroke and anti-aliasing, only pixels in image are visited
+    let margin = if stroke.is_some() { half_stroke } else { 0.0 } + 1.0;
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    let x_start = ((bounds.0 - margin).floor() as i64).clamp(0, width);
+    let y_start = ((bounds.1 - margin).floor() as i64).clamp(0, height);
+    let x_end = ((bounds.2 + margin).ceil() as i64).clamp(0, width);
+    let y_end = ((bounds.3 + margin).ceil() as i64).clamp(0, height);
+
+    for y in y_start..y_end {
+        for x in x_start..x_end {
+            let d = distance(x as f32 + 0.5, y as f32 + 0.5);
+            let fill_coverage = (0.5 - d).clamp(0.0, 1.0);
+            let stroke_coverage = (half_stroke + 0.5 - d.abs()).clamp(0.0, 1.0);
+
+            let layers = [(fill, fill_coverage), (stroke, stroke_coverage)];
+            if !layers.iter().any(|(c, cov)| c.is_some() && *cov > 0.0) {
+                continue;
+            }
+
+            for (color, coverage) in layers {
+                if let (Some(color), true) = (color, coverage > 0.0) {
+                    let alpha = (color[3] as f32 * coverage).round() as u8;
+                    let base = image.get_pixel(x as u32, y as u32);
+                    let blended = get_alpha_color(base, [color[0], color[1], color[2], alpha]);
+                    image.put_pixel(x as u32, y as u32, Rgba(blended));
+                }
+            }
+        }
+    }
+
+    // Area of shape (with stroke) out of image, from bounds instead of visiting every pixel
+    let extend = if stroke.is_some() { half_stroke } else { 0.0 };
+    let (x0, y0) = (bounds.0 - extend, bounds.1 - extend);
+    let (x1, y1) = (bounds.2 + extend, bounds.3 + extend);
+    let area = |x0: f32, y0: f32, x1: f32, y1: f32| {
+        (x1 as f64 - x0 as f64).max(0.0) * (y1 as f64 - y0 as f64).max(0.0)
+    };
+    let area_in = area(
+        x0.max(0.0),
+        y0.max(0.0),
+        x1.min(canvas.width),
+        y1.min(canvas.height),
+    );
+    // Float to int cast saturates at u32::MAX
+    Ok((area(x0, y0, x1, y1) - area_in).round() as u32)
+}
+
+// Approximate signed distance from point (relative to center) to ellipse
+fn ellipse_distance(px: f32, py: f32, rx: f32, ry: f32) -> f32 {
+    if rx <= 0.0 || ry <= 0.0 {
+        return f32::MAX;
+    }
+    let k0 = ((px / rx).powi(2) + (py / ry).powi(2)).sqrt();
+    if k0 == 0.0 {
+        return -rx.min(ry);
+    }
+    let k1 = ((px / (rx * rx)).powi(2) + (py / (ry * ry)).powi(2)).sqrt();
+    k0 * (k0 - 1.0) / k1
+}
+
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let ab = (b.0 - a.0, b.1 - a.1);
+    let ap = (p.0 - a.0, p.1 - a.1);
+    let length = ab.0 * ab.0 + ab.1 * ab.1;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        ((ap.0 * ab.0 + ap.1 * ab.1) / length).clamp(0.0, 1.0)
+    };
+    ((ap.0 - ab.0 * t).powi(2) + (ap.1 - ab.1 * t).powi(2)).sqrt()
+}
+
+// Even-odd rule
+fn point_in_polygon(p: (f32, f32), points: &[(f32, f32)]) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (a, b) = (points[i], points[j]);
+        if (a.1 > p.1) != (b.1 > p.1) && p.0 < (b.0 - a.0) * (p.1 - a.1) / (b.1 - a.1) + a.0 {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(json: &str) -> Shape {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn parse_color_hex() {
+        assert_eq!(parse_color("ff0000").unwrap(), [255, 0, 0, 255]);
+        assert_eq!(parse_color("#00ff0080").unwrap(), [0, 255, 0, 128]);
+        assert!(parse_color("fff").is_err());
+        assert!(parse_color("gg0000").is_err());
+    }
+
+    #[test]
+    fn draw_rect_in_image() {
+        let mut image = DynamicImage::new_rgba8(10, 10);
+        let rect =
+            shape(r#"{"shape": "rect", "x": 20, "y": 20, "w": 50, "h": 50, "fill": "ff0000"}"#);
+        assert_eq!(draw_shape(&mut image, &rect).unwrap(), 0);
+        assert_eq!(image.get_pixel(4, 4), Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(8, 8), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn draw_circle_out_of_image() {
+        let mut image = DynamicImage::new_rgba8(10, 10);
+        let circle = shape(r#"{"shape": "circle", "cx": 0, "cy": 50, "r": 20, "fill": "0000ff"}"#);
+        assert!(draw_shape(&mut image, &circle).unwrap() > 0);
+        assert_eq!(image.get_pixel(0, 5), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn draw_huge_shape_clipped() {
+        // Only pixels of image are visited, out area saturates instead of overflowing
+        let mut image = DynamicImage::new_rgba8(10, 10);
+        let circle =
+            shape(r#"{"shape": "circle", "cx": 50, "cy": 50, "r": 1e9, "fill": "00ff00"}"#);
+        assert_eq!(draw_shape(&mut image, &circle).unwrap(), u32::MAX);
+        assert_eq!(image.get_pixel(9, 9), Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn draw_shape_errors() {
+        let mut image = DynamicImage::new_rgba8(10, 10);
+        let polyline = shape(r#"{"shape": "polyline", "pts": [[0, 0]]}"#);
+        assert!(draw_shape(&mut image, &polyline).is_err());
+        let line =
+            shape(r#"{"shape": "line", "x1": 0, "y1": 0, "x2": 100, "y2": 100, "stroke": "red"}"#);
+        assert_eq!(
+            draw_shape(&mut image, &line).unwrap_err(),
+            "Invalid color: \"red\""
+        );
+    }
+}
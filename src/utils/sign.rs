@@ -0,0 +1,228 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Separator between content and appended signature (not in base64url alphabet)
+pub const SEPARATOR: char = '.';
+
+/// Read key from `env.NAME` (environment variable) or file path.
+/// A line break at the end (e.g. from `echo`) is removed from text keys (environment variable,
+/// PEM or other text file), binary key files (e.g. 32 bytes Ed25519 seed) are kept as is
+pub fn read_key(source: &str) -> Result<Vec<u8>, String> {
+    let mut key = match source.strip_prefix("env.") {
+        Some(name) => std::env::var(name)
+            .map_err(|_| format!("Environment variable \"{}\" is not set", name))?
+            .into_bytes(),
+        None => std::fs::read(source)
+            .map_err(|e| format!("Cannot read key file \"{}\": {}", source, e))?,
+    };
+    let is_text = std::str::from_utf8(&key)
+        .is_ok_and(|text| !text.chars().any(|c| c.is_control() && !c.is_whitespace()));
+    while is_text && key.last().is_some_and(|b| *b == b'\n' || *b == b'\r') {
+        key.pop();
+    }
+    if key.is_empty() {
+        return Err(format!("Key \"{}\" is empty", source));
+    }
+    Ok(key)
+}
+
+fn hmac_sha256(key: &[u8]) -> Hmac<Sha256> {
+    // HMAC accepts key of any length
+    Hmac::<Sha256>::new_from_slice(key).expect("HMAC key of any length")
+}
+
+fn unknown_algorithm(algorithm: &str) -> String {
+    format!(
+        "Unknown signature algorithm: \"{}\" (hmac|ed25519)",
+        algorithm
+    )
+}
+
+// Private key as PKCS#8 PEM (`openssl genpkey -algorithm ed25519`) or 32 bytes seed
+fn signing_key(key: &[u8]) -> Result<SigningKey, String> {
+    if let Ok(seed) = <[u8; 32]>::try_from(key) {
+        return Ok(SigningKey::from_bytes(&seed));
+    }
+    let pem = std::str::from_utf8(key).map_err(|_| "Invalid Ed25519 private key".to_string())?;
+    SigningKey::from_pkcs8_pem(pem).map_err(|e| format!("Invalid Ed25519 private key: {}", e))
+}
+
+// Public key as SPKI PEM (`openssl pkey -pubout`) or 32 bytes
+fn verifying_key(key: &[u8]) -> Result<VerifyingKey, String> {
+    if let Ok(bytes) = <[u8; 32]>::try_from(key) {
+        return VerifyingKey::from_bytes(&bytes)
+            .map_err(|e| format!("Invalid Ed25519 public key: {}", e));
+    }
+    let pem = std::str::from_utf8(key).map_err(|_| "Invalid Ed25519 public key".to_string())?;
+    VerifyingKey::from_public_key_pem(pem).map_err(|e| format!("Invalid Ed25519 public key: {}", e))
+}
+
+/// Key for signing, read and parsed once
+pub enum Signer {
+    /// HMAC-SHA256 with secret key
+    Hmac(Vec<u8>),
+    /// Ed25519 with private key
+    Ed25519(SigningKey),
+}
+
+impl Signer {
+    /// Algorithm `hmac` or `ed25519`, key source is file path or `env.NAME`
+    pub fn new(algorithm: &str, key_source: &str) -> Result<Self, String> {
+        match algorithm {
+            "hmac" => Ok(Self::Hmac(read_key(key_source)?)),
+            "ed25519" => Ok(Self::Ed25519(signing_key(&read_key(key_source)?)?)),
+            v => Err(unknown_algorithm(v)),
+        }
+    }
+
+    /// Signature of content as base64url without padding
+    pub fn signature(&self, content: &str) -> String {
+        let signature = match self {
+            Self::Hmac(key) => {
+                let mut mac = hmac_sha256(key);
+                mac.update(content.as_bytes());
+                mac.finalize().into_bytes().to_vec()
+            }
+            Self::Ed25519(key) => key.sign(content.as_bytes()).to_bytes().to_vec(),
+        };
+        URL_SAFE_NO_PAD.encode(signature)
+    }
+
+    /// Content with appended signature (`content.signature`), checked by `Verifier::verify`
+    pub fn sign(&self, content: &str) -> String {
+        format!("{}{}{}", content, SEPARATOR, self.signature(content))
+    }
+}
+
+// Algorithm only, key is not printed
+impl std::fmt::Debug for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Hmac(_) => write!(f, "Signer::Hmac"),
+            Self::Ed25519(_) => write!(f, "Signer::Ed25519"),
+        }
+    }
+}
+
+// Loaded signers by (algorithm, key source)
+type Signers = HashMap<(String, String), Arc<Signer>>;
+
+/// Signer of algorithm and key source, the key is read once and shared by every row
+pub fn signer(algorithm: &str, key_source: &str) -> Result<Arc<Signer>, String> {
+    static SIGNERS: OnceLock<Mutex<Signers>> = OnceLock::new();
+    let mut signers = SIGNERS.get_or_init(Default::default).lock().unwrap();
+    let id = (algorithm.to_string(), key_source.to_string());
+    if let Some(signer) = signers.get(&id) {
+        return Ok(signer.clone());
+    }
+    let signer = Arc::new(Signer::new(algorithm, key_source)?);
+    signers.insert(id, signer.clone());
+    Ok(signer)
+}
+
+/// Key for verifying, read and parsed once
+pub enum Verifier {
+    /// HMAC-SHA256 with secret key
+    Hmac(Vec<u8>),
+    /// Ed25519 with public key
+    Ed25519(VerifyingKey),
+}
+
+impl Verifier {
+    /// Algorithm `hmac` (secret key) or `ed25519` (public key), key source is file path or `env.NAME`
+    pub fn new(algorithm: &str, key_source: &str) -> Result<Self, String> {
+        match algorithm {
+            "hmac" => Ok(Self::Hmac(read_key(key_source)?)),
+            "ed25519" => Ok(Self::Ed25519(verifying_key(&read_key(key_source)?)?)),
+            v => Err(unknown_algorithm(v)),
+        }
+    }
+
+    /// Verify content with appended signature (`content.signature`), returns content without signature
+    pub fn verify(&self, signed: &str) -> Result<String, String> {
+        let (content, signature) = signed
+            .rsplit_once(SEPARATOR)
+            .ok_or("Content has no signature".to_string())?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| "Signature is not base64url".to_string())?;
+
+        let valid = match self {
+            Self::Hmac(key) => {
+                let mut mac = hmac_sha256(key);
+                mac.update(content.as_bytes());
+                mac.verify_slice(&signature).is_ok()
+            }
+            Self::Ed25519(key) => Signature::from_slice(&signature)
+                .is_ok_and(|signature| key.verify(content.as_bytes(), &signature).is_ok()),
+        };
+        if valid {
+            Ok(content.to_string())
+        } else {
+            Err("Invalid signature".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::TempDir;
+
+    #[test]
+    fn text_key_line_break_removed() {
+        let dir = TempDir::new("sign-text");
+        let path = dir.file("text.key", b"topsecret\n");
+        assert_eq!(read_key(&path).unwrap(), b"topsecret");
+    }
+
+    #[test]
+    fn binary_key_kept() {
+        let mut seed = [7u8; 32];
+        seed[0] = 0xff;
+        seed[31] = b'\n';
+        let dir = TempDir::new("sign-binary");
+        let path = dir.file("seed.key", &seed);
+        assert_eq!(read_key(&path).unwrap(), seed);
+        assert!(Signer::new("ed25519", &path).is_ok());
+    }
+
+    #[test]
+    fn hmac_rfc4231() {
+        // RFC 4231 test case 2
+        let dir = TempDir::new("sign-hmac");
+        let path = dir.file("jefe.key", b"Jefe");
+        let signer = Signer::new("hmac", &path).unwrap();
+        let expected = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+        let expected: Vec<u8> = (0..64)
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&expected[i..i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(
+            signer.signature("what do ya want for nothing?"),
+            URL_SAFE_NO_PAD.encode(expected)
+        );
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let dir = TempDir::new("sign-ed25519");
+        let seed = dir.file("sign.key", &[42u8; 32]);
+        let public = dir.file(
+            "verify.key",
+            SigningKey::from_bytes(&[42u8; 32])
+                .verifying_key()
+                .as_bytes(),
+        );
+        let signed = Signer::new("ed25519", &seed).unwrap().sign("T-001|Row 5");
+        let verifier = Verifier::new("ed25519", &public).unwrap();
+        assert_eq!(verifier.verify(&signed).unwrap(), "T-001|Row 5");
+        assert!(verifier.verify(&signed.replace("001", "002")).is_err());
+        assert!(verifier.verify("T-001").is_err());
+    }
+}
@@ -1,8 +1,1177 @@
-pub fn from_vec(row: Vec<String>, template: &String, row_index: usize) -> String {
-    let mut output: String = template.to_string();
-    for (index_col, col) in row.into_iter().enumerate() {
-        output = output.replace(&format!("{{{}}}", format!("{{{}}}", index_col + 1)), &col);
-        output = output.replace(&"{{ROW}}", &(row_index + 1).to_string());
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use super::payload::uri::percent_encode;
+use super::sign::{signer, Signer};
+
+// Node of parsed template
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    /// `{{ name | filter }}`
+    Placeholder(Expression),
+    /// `{% if condition %}...{% elif condition %}...{% else %}...{% endif %}`
+    If {
+        condition: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    /// `{% for item in name | split: "," %}...{% endfor %}`
+    For {
+        variable: String,
+        source: Expression,
+        separator: String,
+        body: Vec<Node>,
+    },
+}
+
+// Column (number or name), `ROW`, loop variable or quoted text, with filters
+#[derive(Debug)]
+struct Expression {
+    name: String,
+    literal: bool,
+    filters: Vec<Filter>,
+}
+
+// `[not] expression [(==|!=) expression]`, true when value is not empty
+#[derive(Debug)]
+struct Condition {
+    negate: bool,
+    left: Expression,
+    compare: Option<(bool, Expression)>,
+}
+
+#[derive(Debug)]
+struct Filter {
+    name: String,
+    args: Vec<String>,
+    /// Key of `hmac` and `ed25519`, loaded when template is parsed
+    signer: Option<Arc<Signer>>,
+}
+
+/// Variables for `{{vars.name}}` (e.g. `vars` of config file)
+pub type Variables = HashMap<String, String>;
+
+/// Position of row being rendered (start at 0)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Position {
+    /// Row in file, for `{{ROW}}`
+    pub row: usize,
+    /// Row in processed rows (after selection), for `{{COUNTER}}`
+    pub counter: usize,
+    /// Number of processed rows, for `{{TOTAL}}`
+    pub total: usize,
+}
+
+// Computed placeholders, a column with the same name takes priority
+const BUILT_IN: [&str; 7] = ["ROW", "COUNTER", "TOTAL", "UUID", "UUID7", "NOW", "TODAY"];
+
+// Name and number of arguments (min, max) of filters
+const FILTERS: [(&str, usize, usize); 15] = [
+    ("upper", 0, 0),
+    ("lower", 0, 0),
+    ("trim", 0, 0),
+    ("truncate", 1, 2),
+    ("pad_left", 1, 2),
+    ("pad_right", 1, 2),
+    ("replace", 2, 2),
+    ("url_encode", 0, 0),
+    ("date", 1, 2),
+    ("default", 1, 1),
+    ("add", 1, 1),
+    ("sha256", 0, 0),
+    ("crc32", 0, 0),
+    ("hmac", 1, 1),
+    ("ed25519", 1, 1),
+];
+
+// Input formats tried by `date` filter when not given
+const DATE_FORMATS: [&str; 6] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d",
+    "%d/%m/%Y",
+    "%Y%m%d",
+];
+
+// Split by separator that is not in quotes
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, c) if c == separator => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+// Argument of filter, quotes are removed
+fn parse_arg(arg: &str) -> String {
+    let arg = arg.trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|q| arg.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+        .unwrap_or(arg)
+        .to_string()
+}
+
+// Position of pattern that is not in quotes
+fn find_unquoted(value: &str, pattern: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, _) if value[i..].starts_with(pattern) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_filter(part: &str, expression: &str) -> Result<Filter, String> {
+    let (filter_name, args) = match part.split_once(':') {
+        Some((filter_name, args)) => (
+            filter_name.trim(),
+            split_unquoted(args, ',')
+                .into_iter()
+                .map(parse_arg)
+                .collect(),
+        ),
+        None => (part.trim(), Vec::new()),
+    };
+
+    let (_, min, max) = FILTERS
+        .iter()
+        .find(|(n, _, _)| *n == filter_name)
+        .ok_or(format!(
+            "Unknown filter \"{}\" in \"{}\"",
+            filter_name, expression
+        ))?;
+    if args.len() < *min || args.len() > *max {
+        return Err(format!(
+            "Filter \"{}\" takes {} arguments in \"{}\"",
+            filter_name,
+            if min == max {
+                min.to_string()
+            } else {
+                format!("{} to {}", min, max)
+            },
+            expression
+        ));
+    }
+    let is_number = |i: usize| args.get(i).is_none_or(|a| a.parse::<usize>().is_ok());
+    if matches!(filter_name, "truncate" | "pad_left" | "pad_right") && !is_number(0) {
+        return Err(format!(
+            "Filter \"{}\" requires a number in \"{}\"",
+            filter_name, expression
+        ));
+    }
+
+    if filter_name == "add" && args[0].parse::<i64>().is_err() {
+        return Err(format!(
+            "Filter \"add\" requires a number in \"{}\"",
+            expression
+        ));
+    }
+
+    // Load key before generating
+    let signer = match filter_name {
+        "hmac" | "ed25519" => Some(
+            signer(filter_name, &args[0]).map_err(|e| format!("{} in \"{}\"", e, expression))?,
+        ),
+        _ => None,
+    };
+
+    let is_date_format = |f: &String| !StrftimeItems::new(f).any(|i| i == Item::Error);
+    if filter_name == "date" && !args.iter().all(is_date_format) {
+        return Err(format!("Invalid date format in \"{}\"", expression));
+    }
+
+    Ok(Filter {
+        name: filter_name.to_string(),
+        args,
+        signer,
+    })
+}
+
+// Parse `name | filter | filter: arg, "arg"`
+fn parse_expression(expression: &str) -> Result<Expression, String> {
+    let mut parts = split_unquoted(expression, '|').into_iter();
+    let name = parts.next().unwrap_or_default().trim();
+    if name.is_empty() {
+        return Err(format!("Missing column in \"{}\"", expression));
+    }
+    let literal = name.len() >= 2
+        && ['"', '\'']
+            .iter()
+            .any(|q| name.starts_with(*q) && name.ends_with(*q));
+
+    let mut filters = parts
+        .map(|part| parse_filter(part, expression))
+        .collect::<Result<Vec<Filter>, String>>()?;
+
+    // Width shorthand `name:05` (zeros) or `name:5` (spaces) is `pad_left` before filters
+    let width = match name.rsplit_once(':').map(|(n, w)| (n, w.trim())) {
+        Some((n, w)) if !literal && !w.is_empty() && w.bytes().all(|b| b.is_ascii_digit()) => {
+            Some((n.trim(), w))
+        }
+        _ => None,
+    };
+    let name = match width {
+        Some((n, w)) => {
+            let pad = if w.starts_with('0') { "0" } else { " " };
+            filters.insert(
+                0,
+                Filter {
+                    name: "pad_left".to_string(),
+                    args: vec![w.trim_start_matches('0').to_string(), pad.to_string()],
+                    signer: None,
+                },
+            );
+            n
+        }
+        None => name,
+    };
+
+    Ok(Expression {
+        name: if literal {
+            parse_arg(name)
+        } else {
+            name.to_string()
+        },
+        literal,
+        filters,
+    })
+}
+
+fn parse_condition(condition: &str) -> Result<Condition, String> {
+    let condition = condition.trim();
+    let (negate, condition) = match condition.strip_prefix("not ") {
+        Some(rest) => (true, rest),
+        None => (false, condition),
+    };
+
+    let compare = ["==", "!="]
+        .iter()
+        .find_map(|op| find_unquoted(condition, op).map(|i| (i, *op)));
+    match compare {
+        Some((i, op)) => Ok(Condition {
+            negate,
+            left: parse_expression(&condition[..i])?,
+            compare: Some((op == "==", parse_expression(&condition[i + 2..])?)),
+        }),
+        None => Ok(Condition {
+            negate,
+            left: parse_expression(condition)?,
+            compare: None,
+        }),
+    }
+}
+
+// Parse `item in name | filter | split: ","`
+fn parse_for(tag: &str) -> Result<Node, String> {
+    let invalid = || format!("Invalid loop: \"{{% {} %}}\" (for item in column)", tag);
+    let (variable, source) = tag
+        .strip_prefix("for ")
+        .and_then(|rest| rest.split_once(" in "))
+        .ok_or_else(invalid)?;
+    let variable = variable.trim();
+    if variable.is_empty() || variable.contains(char::is_whitespace) {
+        return Err(invalid());
+    }
+
+    // `split` is only for loop, must be the last filter
+    let mut parts = split_unquoted(source, '|');
+    let mut separator = ",".to_string();
+    if let Some(last) = parts.last() {
+        if let Some(args) = last.trim().strip_prefix("split") {
+            separator = args
+                .trim()
+                .strip_prefix(':')
+                .map(parse_arg)
+                .filter(|s| !s.is_empty())
+                .ok_or_else(invalid)?;
+            parts.pop();
+        }
+    }
+
+    Ok(Node::For {
+        variable: variable.to_string(),
+        source: parse_expression(&parts.join("|"))?,
+        separator,
+        body: Vec::new(),
+    })
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+    Tag(&'a str),
+}
+
+// Split template to text, `{{ }}` and `{% %}`
+fn tokenize(template: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    loop {
+        let start = match (rest.find("{{"), rest.find("{%")) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => break,
+        };
+        let is_tag = rest[start..].starts_with("{%");
+        let close = if is_tag { "%}" } else { "}}" };
+        let Some(end) = rest[start + 2..].find(close) else {
+            return Err(format!(
+                "Missing \"{}\" in template: \"{}\"",
+                close, template
+            ));
+        };
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        let inner = &rest[start + 2..start + 2 + end];
+        tokens.push(if is_tag {
+            Token::Tag(inner.trim())
+        } else {
+            Token::Placeholder(inner)
+        });
+        rest = &rest[start + 2 + end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+// Parse nodes until one of end tags, return nodes and the end tag found
+fn parse_nodes<'a>(
+    tokens: &mut std::vec::IntoIter<Token<'a>>,
+    end_tags: &[&str],
+) -> Result<(Vec<Node>, Option<&'a str>), String> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.to_string())),
+            Token::Placeholder(p) => nodes.push(Node::Placeholder(parse_expression(p)?)),
+            Token::Tag(tag) => {
+                let keyword = tag.split_whitespace().next().unwrap_or_default();
+                if end_tags.contains(&keyword) {
+                    return Ok((nodes, Some(tag)));
+                }
+                match keyword {
+                    "if" => nodes.push(parse_if(&tag[2..], tokens)?),
+                    "for" => {
+                        let mut node = parse_for(tag)?;
+                        let (body, end) = parse_nodes(tokens, &["endfor"])?;
+                        if end.is_none() {
+                            return Err(format!(
+                                "Missing \"{{% endfor %}}\" for \"{{% {} %}}\"",
+                                tag
+                            ));
+                        }
+                        if let Node::For { body: b, .. } = &mut node {
+                            *b = body;
+                        }
+                        nodes.push(node);
+                    }
+                    _ => return Err(format!("Unexpected \"{{% {} %}}\" in template", tag)),
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+// Parse if block after `{% if`, `elif` is nested if in otherwise
+fn parse_if<'a>(
+    condition: &str,
+    tokens: &mut std::vec::IntoIter<Token<'a>>,
+) -> Result<Node, String> {
+    let condition = parse_condition(condition)?;
+    let (then, end) = parse_nodes(tokens, &["elif", "else", "endif"])?;
+    let otherwise = match end {
+        None => return Err("Missing \"{% endif %}\" in template".to_string()),
+        Some("endif") => Vec::new(),
+        Some("else") => match parse_nodes(tokens, &["endif"])? {
+            (nodes, Some(_)) => nodes,
+            (_, None) => return Err("Missing \"{% endif %}\" in template".to_string()),
+        },
+        Some(tag) => vec![parse_if(&tag[4..], tokens)?],
+    };
+    Ok(Node::If {
+        condition,
+        then,
+        otherwise,
+    })
+}
+
+// Parse template to nodes
+fn parse(template: &str) -> Result<Vec<Node>, String> {
+    let mut tokens = tokenize(template)?.into_iter();
+    match parse_nodes(&mut tokens, &[])? {
+        (nodes, None) => Ok(nodes),
+        (_, Some(tag)) => Err(format!("Unexpected \"{{% {} %}}\" in template", tag)),
+    }
+}
+
+fn apply_filter(value: String, filter: &Filter) -> Result<String, String> {
+    let arg = |i: usize| filter.args.get(i).map(String::as_str);
+    let number = |i: usize| arg(i).and_then(|a| a.parse::<usize>().ok()).unwrap_or(0);
+    let pad = || arg(1).and_then(|a| a.chars().next()).unwrap_or(' ');
+
+    Ok(match filter.name.as_str() {
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        "trim" => value.trim().to_string(),
+        "truncate" => {
+            if value.chars().count() > number(0) {
+                value.chars().take(number(0)).collect::<String>() + arg(1).unwrap_or_default()
+            } else {
+                value
+            }
+        }
+        "pad_left" => {
+            let count = number(0).saturating_sub(value.chars().count());
+            std::iter::repeat_n(pad(), count).collect::<String>() + &value
+        }
+        "pad_right" => {
+            let count = number(0).saturating_sub(value.chars().count());
+            value + &std::iter::repeat_n(pad(), count).collect::<String>()
+        }
+        "replace" => value.replace(arg(0).unwrap_or_default(), arg(1).unwrap_or_default()),
+        "url_encode" => percent_encode(&value),
+        "date" => format_date(&value, arg(0).unwrap_or_default(), arg(1)).unwrap_or(value),
+        "default" if value.trim().is_empty() => arg(0).unwrap_or_default().to_string(),
+        "add" => match (
+            value.trim().parse::<i64>(),
+            arg(0).unwrap_or_default().parse::<i64>(),
+        ) {
+            (Ok(v), Ok(n)) => v
+                .checked_add(n)
+                .ok_or(format!("Filter \"add\" overflows: {} + {}", v, n))?
+                .to_string(),
+            _ => value,
+        },
+        "sha256" => hex(&Sha256::digest(value.as_bytes())),
+        "crc32" => format!("{:08x}", crc32(value.as_bytes())),
+        "hmac" | "ed25519" => match &filter.signer {
+            Some(signer) => signer.signature(&value),
+            None => return Err(format!("Key of filter \"{}\" is not loaded", filter.name)),
+        },
+        _ => value,
+    })
+}
+
+// Format date value, None when value is not a date
+fn format_date(value: &str, format: &str, input_format: Option<&str>) -> Option<String> {
+    let value = value.trim();
+    let formats = match input_format {
+        Some(f) => vec![f],
+        None => DATE_FORMATS.to_vec(),
+    };
+    let date = formats.iter().find_map(|f| {
+        NaiveDateTime::parse_from_str(value, f).ok().or_else(|| {
+            NaiveDate::parse_from_str(value, f)
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+    })?;
+
+    // Invalid format specifier makes `to_string` panic, check with write
+    let mut output = String::new();
+    std::fmt::write(&mut output, format_args!("{}", date.format(format))).ok()?;
+    Some(output)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// CRC-32 (IEEE 802.3)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+// Value of `env.NAME` (environment variable) or `vars.name` (config variable),
+// None when name is not one of them
+fn external(name: &str, vars: &Variables) -> Option<Result<String, String>> {
+    match name.split_once('.') {
+        Some(("env", key)) => Some(
+            std::env::var(key).map_err(|_| format!("Environment variable \"{}\" is not set", key)),
+        ),
+        Some(("vars", key)) => Some(
+            vars.get(key)
+                .cloned()
+                .ok_or(format!("Variable \"{}\" is not defined in vars", key)),
+        ),
+        _ => None,
+    }
+}
+
+// Time of this run, same for every row
+fn now() -> &'static chrono::DateTime<Local> {
+    static NOW: OnceLock<chrono::DateTime<Local>> = OnceLock::new();
+    NOW.get_or_init(Local::now)
+}
+
+// Random bytes of row, the same row gets the same bytes in every template of this run
+fn random_bytes(row: usize, name: &str) -> [u8; 16] {
+    static SEED: OnceLock<[u8; 32]> = OnceLock::new();
+    let seed = SEED.get_or_init(|| {
+        let mut seed = [0u8; 32];
+        getrandom::fill(&mut seed).expect("Cannot get random bytes");
+        seed
+    });
+    let digest = Sha256::new()
+        .chain_update(seed)
+        .chain_update(row.to_be_bytes())
+        .chain_update(name.as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes
+}
+
+// UUID version 4 (random) or 7 (time ordered)
+fn uuid(row: usize, version: u8) -> String {
+    let mut bytes = random_bytes(row, if version == 7 { "UUID7" } else { "UUID" });
+    if version == 7 {
+        let millis = now().timestamp_millis() as u64;
+        bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    }
+    bytes[6] = (bytes[6] & 0x0f) | (version << 4);
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+// Data of row and loop variables for rendering
+struct Context<'a> {
+    row: &'a [String],
+    headers: &'a [String],
+    position: &'a Position,
+    vars: &'a Variables,
+    /// Loop variables (name, value, index), inner loop is last
+    loops: Vec<(String, String, usize)>,
+}
+
+impl Context<'_> {
+    fn value(&self, expression: &Expression) -> Result<String, String> {
+        let value = if expression.literal {
+            expression.name.clone()
+        } else {
+            self.lookup(&expression.name)?
+        };
+        expression.filters.iter().try_fold(value, apply_filter)
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        if let Some((_, value, _)) = self.loops.iter().rev().find(|(n, _, _)| n == name) {
+            return Ok(value.clone());
+        }
+        if name == "loop.index" {
+            if let Some((_, _, index)) = self.loops.last() {
+                return Ok((index + 1).to_string());
+            }
+        }
+        let index_col = match name.parse::<usize>() {
+            Ok(number) => number.checked_sub(1),
+            Err(_) => self.headers.iter().position(|h| *h == name),
+        };
+        if let Some(col) = index_col.and_then(|i| self.row.get(i)) {
+            return Ok(col.clone());
+        }
+        Ok(match name {
+            "ROW" => (self.position.row + 1).to_string(),
+            "COUNTER" => (self.position.counter + 1).to_string(),
+            "TOTAL" => self.position.total.to_string(),
+            "UUID" => uuid(self.position.row, 4),
+            "UUID7" => uuid(self.position.row, 7),
+            "NOW" => now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "TODAY" => now().format("%Y-%m-%d").to_string(),
+            _ => external(name, self.vars).transpose()?.unwrap_or_default(),
+        })
+    }
+
+    fn is_true(&self, condition: &Condition) -> Result<bool, String> {
+        let left = self.value(&condition.left)?;
+        let result = match &condition.compare {
+            Some((equal, right)) => (left == self.value(right)?) == *equal,
+            None => !left.trim().is_empty(),
+        };
+        Ok(result != condition.negate)
+    }
+
+    fn render(&mut self, nodes: &[Node], output: &mut String) -> Result<(), String> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Placeholder(expression) => output.push_str(&self.value(expression)?),
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    if self.is_true(condition)? {
+                        self.render(then, output)?;
+                    } else {
+                        self.render(otherwise, output)?;
+                    }
+                }
+                Node::For {
+                    variable,
+                    source,
+                    separator,
+                    body,
+                } => {
+                    let value = self.value(source)?;
+                    let items = value
+                        .split(separator.as_str())
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty());
+                    for (index, item) in items.enumerate() {
+                        self.loops.push((variable.clone(), item.to_string(), index));
+                        self.render(body, output)?;
+                        self.loops.pop();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parsed template, parse once and render it for every row.
+/// `{{1}}` is column number, `{{name}}` is column name (with header) and `{{ROW}}` is row number,
+/// computed: `{{COUNTER}}`, `{{TOTAL}}`, `{{UUID}}`, `{{UUID7}}`, `{{NOW}}` and `{{TODAY}}`,
+/// `{{env.NAME}}` is environment variable and `{{vars.name}}` is variable of config file.
+/// Filters can be chained, e.g. `{{ 2 | trim | lower | replace: " ", "_" }}`,
+/// `{{ 1:05 }}` pads with zeros to 5 characters.
+/// Blocks: `{% if 3 %}VIP{% else %}Guest{% endif %}`, `{% for tag in 4 %}#{{tag}} {% endfor %}`.
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Error when template is invalid (see `check_columns` to check columns before rendering rows)
+    pub fn parse(template: &str) -> Result<Self, String> {
+        Ok(Self {
+            nodes: parse(template)?,
+        })
+    }
+
+    /// Render template with data of row
+    pub fn render(
+        &self,
+        row: &[String],
+        headers: &[String],
+        position: &Position,
+        vars: &Variables,
+    ) -> Result<String, String> {
+        let mut context = Context {
+            row,
+            headers,
+            position,
+            vars,
+            loops: Vec::new(),
+        };
+        let mut output = String::new();
+        context.render(&self.nodes, &mut output)?;
+        Ok(output)
+    }
+}
+
+/// Render template with data of row (parse once with `Template::parse` to render many rows)
+pub fn from_vec(
+    row: Vec<String>,
+    headers: &[String],
+    template: &str,
+    position: &Position,
+    vars: &Variables,
+) -> Result<String, String> {
+    Template::parse(template)?.render(&row, headers, position, vars)
+}
+
+// Condition of array item from its `if` key
+#[derive(Debug)]
+enum Keep {
+    Bool(bool),
+    If(Condition),
+}
+
+#[derive(Debug)]
+enum ValueNode {
+    String(Template),
+    Array(Vec<(Keep, ValueNode)>),
+    Object(Vec<(String, ValueNode)>),
+    Other(Value),
+}
+
+impl ValueNode {
+    fn parse(value: &Value) -> Result<Self, String> {
+        Ok(match value {
+            Value::String(template) => Self::String(Template::parse(template)?),
+            Value::Array(items) => Self::Array(
+                items
+                    .iter()
+                    .map(|item| {
+                        let keep = match item.get("if") {
+                            Some(Value::String(c)) => Keep::If(parse_condition(c)?),
+                            Some(Value::Bool(b)) => Keep::Bool(*b),
+                            _ => Keep::Bool(true),
+                        };
+                        Ok((keep, Self::parse(item)?))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+            Value::Object(object) => Self::Object(
+                object
+                    .iter()
+                    .filter(|(k, _)| *k != "if")
+                    .map(|(k, v)| Ok((k.clone(), Self::parse(v)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            v => Self::Other(v.clone()),
+        })
+    }
+
+    fn render(&self, context: &mut Context) -> Result<Value, String> {
+        Ok(match self {
+            Self::String(template) => {
+                let mut output = String::new();
+                context.render(&template.nodes, &mut output)?;
+                Value::String(output)
+            }
+            Self::Array(items) => {
+                let mut values = Vec::with_capacity(items.len());
+                for (keep, item) in items {
+                    let keep = match keep {
+                        Keep::Bool(b) => *b,
+                        Keep::If(condition) => context.is_true(condition)?,
+                    };
+                    if keep {
+                        values.push(item.render(context)?);
+                    }
+                }
+                Value::Array(values)
+            }
+            Self::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), v.render(context)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            Self::Other(v) => v.clone(),
+        })
+    }
+}
+
+/// Parsed template of every string in value (e.g. draw template), keys and other types are kept.
+/// Items of array that have `if` key (e.g. `{if: "3"}`, `{if: "type == 'VIP'"}`)
+/// are kept only when condition is true.
+#[derive(Debug)]
+pub struct ValueTemplate(ValueNode);
+
+impl ValueTemplate {
+    pub fn parse(value: &Value) -> Result<Self, String> {
+        Ok(Self(ValueNode::parse(value)?))
+    }
+
+    /// Render templates with data of row
+    pub fn render(
+        &self,
+        row: &[String],
+        headers: &[String],
+        position: &Position,
+        vars: &Variables,
+    ) -> Result<Value, String> {
+        let mut context = Context {
+            row,
+            headers,
+            position,
+            vars,
+            loops: Vec::new(),
+        };
+        self.0.render(&mut context)
+    }
+}
+
+// Check that columns of expression exist, loop variables are in scope
+fn check_expression(
+    expression: &Expression,
+    scope: &[&str],
+    headers: &[String],
+    count_col: usize,
+    vars: &Variables,
+) -> Result<(), String> {
+    let name = expression.name.as_str();
+    let exists = expression.literal
+        || scope.contains(&name)
+        || (name == "loop.index" && !scope.is_empty())
+        || match name.parse::<usize>() {
+            Ok(number) => number >= 1 && number <= count_col,
+            Err(_) => BUILT_IN.contains(&name) || headers.iter().any(|h| h == name),
+        };
+    if exists {
+        return Ok(());
+    }
+    if let Some(result) = external(name, vars) {
+        return result.map(|_| ());
+    }
+    Err(if headers.is_empty() {
+        format!(
+            "Column \"{{{{{}}}}}\" does not exist (file has {} columns)",
+            name, count_col
+        )
+    } else {
+        format!(
+            "Column \"{{{{{}}}}}\" does not exist, available: {}",
+            name,
+            headers.join(", ")
+        )
+    })
+}
+
+fn check_nodes<'a>(
+    nodes: &'a [Node],
+    scope: &mut Vec<&'a str>,
+    headers: &[String],
+    count_col: usize,
+    vars: &Variables,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Placeholder(expression) => {
+                check_expression(expression, scope, headers, count_col, vars)?
+            }
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                check_expression(&condition.left, scope, headers, count_col, vars)?;
+                if let Some((_, right)) = &condition.compare {
+                    check_expression(right, scope, headers, count_col, vars)?;
+                }
+                check_nodes(then, scope, headers, count_col, vars)?;
+                check_nodes(otherwise, scope, headers, count_col, vars)?;
+            }
+            Node::For {
+                variable,
+                source,
+                body,
+                ..
+            } => {
+                check_expression(source, scope, headers, count_col, vars)?;
+                scope.push(variable);
+                check_nodes(body, scope, headers, count_col, vars)?;
+                scope.pop();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check template syntax, filters and that every column referenced exists
+/// (by number, or by name from header row)
+pub fn check_columns(
+    template: &str,
+    headers: &[String],
+    count_col: usize,
+    vars: &Variables,
+) -> Result<(), String> {
+    check_nodes(&parse(template)?, &mut Vec::new(), headers, count_col, vars)
+}
+
+/// Check templates in string values and conditions of `if` keys of parsed draw template
+pub fn check_value(
+    value: &Value,
+    headers: &[String],
+    count_col: usize,
+    vars: &Variables,
+) -> Result<(), String> {
+    match value {
+        Value::String(template) => check_columns(template, headers, count_col, vars),
+        Value::Array(items) => items
+            .iter()
+            .try_for_each(|v| check_value(v, headers, count_col, vars)),
+        Value::Object(object) => object.iter().try_for_each(|(k, v)| match (k.as_str(), v) {
+            ("if", Value::String(c)) => check_condition(c, headers, count_col, vars),
+            _ => check_value(v, headers, count_col, vars),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check syntax and columns of condition (`if` key of draw template item)
+pub fn check_condition(
+    condition: &str,
+    headers: &[String],
+    count_col: usize,
+    vars: &Variables,
+) -> Result<(), String> {
+    let condition = parse_condition(condition)?;
+    check_expression(&condition.left, &[], headers, count_col, vars)?;
+    match &condition.compare {
+        Some((_, right)) => check_expression(right, &[], headers, count_col, vars),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, row: &[&str]) -> Result<String, String> {
+        let row = row.iter().map(|v| v.to_string()).collect();
+        let headers = ["name".to_string(), "n".to_string()];
+        from_vec(
+            row,
+            &headers,
+            template,
+            &Position::default(),
+            &Variables::new(),
+        )
+    }
+
+    #[test]
+    fn render_row() {
+        assert_eq!(
+            render("{{name | upper}}-{{2:04}}", &["ann", "7"]).unwrap(),
+            "ANN-0007"
+        );
+        assert_eq!(
+            render(
+                "{% if n == '7' %}lucky{% else %}{{n}}{% endif %}",
+                &["ann", "7"]
+            )
+            .unwrap(),
+            "lucky"
+        );
+    }
+
+    #[test]
+    fn add_overflow_is_error() {
+        assert_eq!(render("{{n | add: 5}}", &["", "-7"]).unwrap(), "-2");
+        assert_eq!(render("{{name | add: 5}}", &["x", ""]).unwrap(), "x");
+        assert!(render("{{n | add: 5}}", &["", "9223372036854775806"]).is_err());
+        assert!(render("{{n | add: -1}}", &["", "-9223372036854775808"]).is_err());
+    }
+
+    #[test]
+    fn invalid_template_is_error() {
+        assert!(render("{{name", &["ann", "7"]).is_err());
+        assert!(render("{{name | unknown}}", &["ann", "7"]).is_err());
+        assert!(render("{% if name %}VIP", &["ann", "7"]).is_err());
+        let value = serde_json::json!([{"if": "name ==", "t": "x"}]);
+        assert!(ValueTemplate::parse(&value).is_err());
+    }
+
+    #[test]
+    fn text_filters() {
+        let row = ["Ann Smith", "7"];
+        assert_eq!(render("{{name | truncate: 3}}", &row).unwrap(), "Ann");
+        assert_eq!(
+            render("{{name | truncate: 3, '..'}}", &row).unwrap(),
+            "Ann.."
+        );
+        assert_eq!(
+            render("{{name | truncate: 20, '..'}}", &row).unwrap(),
+            "Ann Smith"
+        );
+        assert_eq!(render("{{n | pad_left: 3}}", &row).unwrap(), "  7");
+        assert_eq!(render("{{n | pad_left: 3, '*'}}", &row).unwrap(), "**7");
+        assert_eq!(render("{{n | pad_right: 3, '-'}}", &row).unwrap(), "7--");
+        assert_eq!(render("{{n:05}}", &row).unwrap(), "00007");
+        assert_eq!(render("{{name:5}}", &row).unwrap(), "Ann Smith");
+        assert_eq!(
+            render("{{name | replace: ' ', ', '}}", &row).unwrap(),
+            "Ann, Smith"
+        );
+        assert_eq!(
+            render("{{name | replace: \"Ann\", \"A,\"}}", &row).unwrap(),
+            "A, Smith"
+        );
+        assert_eq!(
+            render("{{name | url_encode}}", &["a b&c=d/é", ""]).unwrap(),
+            "a%20b%26c%3Dd%2F%C3%A9"
+        );
+        assert_eq!(render("{{n | default: 'none'}}", &row).unwrap(), "7");
+        assert_eq!(
+            render("{{n | default: 'none'}}", &["", " "]).unwrap(),
+            "none"
+        );
+    }
+
+    #[test]
+    fn date_filter() {
+        let date = |template: &str, value: &str| render(template, &[value, ""]).unwrap();
+        assert_eq!(
+            date("{{name | date: '%d/%m/%Y'}}", "2025-03-09"),
+            "09/03/2025"
+        );
+        assert_eq!(
+            date("{{name | date: '%H:%M'}}", "2025-03-09T14:05:00"),
+            "14:05"
+        );
+        assert_eq!(
+            date("{{name | date: '%Y-%m-%d', '%m.%d.%Y'}}", "03.09.2025"),
+            "2025-03-09"
+        );
+        // Not a date in input format, value is kept
+        assert_eq!(
+            date("{{name | date: '%Y', '%m.%d.%Y'}}", "2025-03-09"),
+            "2025-03-09"
+        );
+        assert_eq!(date("{{name | date: '%Y'}}", "soon"), "soon");
+        assert!(render("{{name | date: '%Q'}}", &["2025-03-09", ""]).is_err());
+    }
+
+    #[test]
+    fn for_loop() {
+        assert_eq!(
+            render(
+                "{% for tag in name %}#{{tag}} {% endfor %}",
+                &["a, b,,c", ""]
+            )
+            .unwrap(),
+            "#a #b #c "
+        );
+        assert_eq!(
+            render(
+                "{% for t in name | split: ';' %}{{loop.index}}.{{t | upper}}{% endfor %}",
+                &["x;y", ""]
+            )
+            .unwrap(),
+            "1.X2.Y"
+        );
+        // Inner loop index
+        assert_eq!(
+            render(
+                "{% for a in name %}{% for b in n | split: '-' %}{{a}}{{loop.index}}{% endfor %} {% endfor %}",
+                &["p,q", "x-y"]
+            )
+            .unwrap(),
+            "p1p2 q1q2 "
+        );
+        assert_eq!(
+            render("{% for t in n %}{{t}}{% endfor %}", &["", ""]).unwrap(),
+            ""
+        );
+        assert!(check_columns("{{loop.index}}", &[], 1, &Variables::new()).is_err());
+        assert!(check_columns(
+            "{% for t in 1 %}{{t}}{% endfor %}{{t}}",
+            &[],
+            1,
+            &Variables::new()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn value_template() {
+        let value = serde_json::json!([
+            {"if": "n == '7'", "text": "lucky {{name}}"},
+            {"if": "not name", "text": "no name"},
+            {"if": false, "text": "never"},
+            {"text": "{{ROW}}", "size": 12},
+        ]);
+        let template = ValueTemplate::parse(&value).unwrap();
+        let headers = ["name".to_string(), "n".to_string()];
+        let row = ["ann".to_string(), "7".to_string()];
+        assert_eq!(
+            template
+                .render(&row, &headers, &Position::default(), &Variables::new())
+                .unwrap(),
+            serde_json::json!([{"text": "lucky ann"}, {"text": "1", "size": 12}])
+        );
+    }
+
+    #[test]
+    fn env_and_vars() {
+        std::env::set_var("QRGEN_TEST_SITE", "example.com");
+        let vars = Variables::from([("campaign".to_string(), "spring".to_string())]);
+        let row = ["ann".to_string()];
+        let headers = ["name".to_string()];
+        let template = "https://{{env.QRGEN_TEST_SITE}}/{{vars.campaign}}/{{name}}";
+        assert_eq!(
+            from_vec(
+                row.to_vec(),
+                &headers,
+                template,
+                &Position::default(),
+                &vars
+            )
+            .unwrap(),
+            "https://example.com/spring/ann"
+        );
+        assert!(check_columns(template, &headers, 1, &vars).is_ok());
+        assert!(check_condition("vars.campaign == 'spring'", &headers, 1, &vars).is_ok());
+
+        // Missing variables are errors when checked and rendered
+        let missing = [
+            (
+                "{{vars.other}}",
+                "Variable \"other\" is not defined in vars",
+            ),
+            (
+                "{{env.QRGEN_TEST_MISSING}}",
+                "Environment variable \"QRGEN_TEST_MISSING\" is not set",
+            ),
+        ];
+        for (template, error) in missing {
+            assert_eq!(
+                check_columns(template, &headers, 1, &vars).unwrap_err(),
+                error
+            );
+            assert_eq!(
+                from_vec(
+                    row.to_vec(),
+                    &headers,
+                    template,
+                    &Position::default(),
+                    &vars
+                )
+                .unwrap_err(),
+                error
+            );
+        }
+        assert!(check_columns("{{vars.campaign}}", &headers, 1, &Variables::new()).is_err());
     }
-    output
 }
@@ -0,0 +1,400 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::shape::{parse_color, Shape};
+use super::template::{check_columns, check_condition, Variables};
+
+/// Problem found by validation, path of value in config is like `common_arg.template_draw[0].c`
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub path: String,
+    pub message: String,
+    /// Not an error, e.g. drawing out of canvas
+    pub warning: bool,
+}
+
+impl Issue {
+    pub fn error(path: &str, message: String) -> Self {
+        Self {
+            path: path.to_string(),
+            message,
+            warning: false,
+        }
+    }
+
+    pub fn warning(path: &str, message: String) -> Self {
+        Self {
+            path: path.to_string(),
+            message,
+            warning: true,
+        }
+    }
+}
+
+// Start of values in JSON5 text, read without building values
+struct Locator {
+    chars: Vec<char>,
+    index: usize,
+    positions: HashMap<String, usize>,
+}
+
+impl Locator {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    // Skip whitespace and comments
+    fn skip_blank(&mut self) {
+        while let Some(c) = self.peek() {
+            let next = self.chars.get(self.index + 1).copied();
+            match (c, next) {
+                (c, _) if c.is_whitespace() => self.index += 1,
+                ('/', Some('/')) => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.index += 1;
+                    }
+                }
+                ('/', Some('*')) => {
+                    self.index += 2;
+                    while self.index < self.chars.len()
+                        && !(self.chars[self.index] == '*'
+                            && self.chars.get(self.index + 1) == Some(&'/'))
+                    {
+                        self.index += 1;
+                    }
+                    self.index += 2;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn string(&mut self) -> String {
+        let quote = self.chars[self.index];
+        self.index += 1;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.index += 1;
+            match c {
+                '\\' => {
+                    if let Some(c) = self.peek() {
+                        text.push(c);
+                        self.index += 1;
+                    }
+                }
+                c if c == quote => break,
+                c => text.push(c),
+            }
+        }
+        text
+    }
+
+    // Unquoted key, number or literal
+    fn word(&mut self) -> String {
+        let start = self.index;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, ',' | ':' | ']' | '}' | '/'))
+        {
+            self.index += 1;
+        }
+        self.chars[start..self.index].iter().collect()
+    }
+
+    fn value(&mut self, path: String) {
+        self.skip_blank();
+        self.positions.insert(path.clone(), self.index);
+        match self.peek() {
+            Some('{') => {
+                self.index += 1;
+                loop {
+                    self.skip_blank();
+                    let key = match self.peek() {
+                        None => return,
+                        Some('}') => break,
+                        Some('"' | '\'') => self.string(),
+                        Some(_) => self.word(),
+                    };
+                    self.skip_blank();
+                    if self.peek() != Some(':') {
+                        return;
+                    }
+                    self.index += 1;
+                    let path = if path.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    self.value(path);
+                    self.skip_blank();
+                    if self.peek() == Some(',') {
+                        self.index += 1;
+                    }
+                }
+                self.index += 1;
+            }
+            Some('[') => {
+                self.index += 1;
+                for item in 0.. {
+                    self.skip_blank();
+                    match self.peek() {
+                        None => return,
+                        Some(']') => break,
+                        Some(_) => self.value(format!("{}[{}]", path, item)),
+                    }
+                    self.skip_blank();
+                    if self.peek() == Some(',') {
+                        self.index += 1;
+                    }
+                }
+                self.index += 1;
+            }
+            Some('"' | '\'') => {
+                self.string();
+            }
+            Some(_) => {
+                self.word();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Position (line, column from 1) of values in JSON5 text by path, text should be valid JSON5
+pub fn locate_values(text: &str) -> HashMap<String, (usize, usize)> {
+    let mut locator = Locator {
+        chars: text.chars().collect(),
+        index: 0,
+        positions: HashMap::new(),
+    };
+    locator.value(String::new());
+
+    let mut line_col = Vec::with_capacity(locator.chars.len() + 1);
+    let (mut line, mut column) = (1, 1);
+    for c in &locator.chars {
+        line_col.push((line, column));
+        if *c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    line_col.push((line, column));
+
+    locator
+        .positions
+        .into_iter()
+        .map(|(path, index)| (path, line_col[index.min(line_col.len() - 1)]))
+        .collect()
+}
+
+/// Position of value, or of the nearest parent found (e.g. value inside a template string)
+pub fn position(positions: &HashMap<String, (usize, usize)>, path: &str) -> Option<(usize, usize)> {
+    let mut path = path;
+    loop {
+        if let Some(position) = positions.get(path) {
+            return Some(*position);
+        }
+        path = &path[..path.rfind(['.', '['])?];
+    }
+}
+
+// Keys of draw template that are percentage of canvas, with the size key of box
+const POSITION_KEYS: [(&str, Option<&str>); 8] = [
+    ("x", Some("w")),
+    ("y", Some("h")),
+    ("cx", None),
+    ("cy", None),
+    ("x1", None),
+    ("y1", None),
+    ("x2", None),
+    ("y2", None),
+];
+
+/// Check draw template: font index `f` (0 is default font, 1 to `font_count` are font files),
+/// colors, positions (percentage of canvas), and placeholders and `if` conditions
+/// when columns (headers, number of columns) are given, with `vars` for `{{vars.name}}`
+pub fn check_draw(
+    value: &Value,
+    path: &str,
+    font_count: usize,
+    columns: Option<(&[String], usize)>,
+    vars: &Variables,
+    issues: &mut Vec<Issue>,
+) {
+    match value {
+        Value::String(template) => {
+            let Some((headers, count_col)) = columns else {
+                return;
+            };
+            if let Err(e) = check_columns(template, headers, count_col, vars) {
+                issues.push(Issue::error(path, e));
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let path = format!("{}[{}]", path, index);
+                check_draw(item, &path, font_count, columns, vars, issues);
+            }
+        }
+        Value::Object(object) => {
+            if object.contains_key("shape") {
+                if let Err(e) = Shape::deserialize(value.clone()) {
+                    issues.push(Issue::error(path, format!("Invalid shape: {}", e)));
+                }
+            }
+
+            for (key, value) in object {
+                let path = format!("{}.{}", path, key);
+                match (key.as_str(), value) {
+                    ("if", Value::String(condition)) => {
+                        let Some((headers, count_col)) = columns else {
+                            continue;
+                        };
+                        if let Err(e) = check_condition(condition, headers, count_col, vars) {
+                            issues.push(Issue::error(&path, e));
+                        }
+                    }
+                    ("f", f) if f.as_u64().is_none_or(|f| f as usize > font_count) => {
+                        issues.push(Issue::error(
+                            &path,
+                            format!(
+                                "Font index {} is out of range (0 to {}, 0 is default font, others are --fp)",
+                                f, font_count
+                            ),
+                        ));
+                    }
+                    ("c" | "fill" | "stroke", Value::String(color)) if !color.contains("{{") => {
+                        if let Err(e) = parse_color(color) {
+                            issues.push(Issue::error(&path, e));
+                        }
+                    }
+                    _ => check_draw(value, &path, font_count, columns, vars, issues),
+                }
+            }
+
+            for (key, size_key) in POSITION_KEYS {
+                let Some(start) = object.get(key).and_then(Value::as_f64) else {
+                    continue;
+                };
+                let size = size_key
+                    .and_then(|k| object.get(k))
+                    .and_then(Value::as_f64)
+                    .unwrap_or(0.0);
+                if start < 0.0 || start + size > 100.0 {
+                    issues.push(Issue::warning(
+                        &format!("{}.{}", path, key),
+                        format!(
+                            "Position {}{} is out of canvas (0 to 100)",
+                            start,
+                            if size > 0.0 {
+                                format!(" + size {}", size)
+                            } else {
+                                String::new()
+                            }
+                        ),
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONFIG: &str = r#"{
+  // comment with "quote" and {brace}
+  command: "from",
+  /* block
+     comment */ 'common_arg': {
+    template_draw: [
+      {t: "say \"hi\", {x}", c: "ff0000"},
+      [1, {f: 2}],
+    ],
+  },
+}"#;
+
+    #[test]
+    fn locate_nested_values() {
+        let positions = locate_values(CONFIG);
+        for (path, expected) in [
+            ("", (1, 1)),
+            ("command", (3, 12)),
+            ("common_arg", (5, 31)),
+            ("common_arg.template_draw", (6, 20)),
+            ("common_arg.template_draw[0]", (7, 7)),
+            ("common_arg.template_draw[0].t", (7, 11)),
+            // After string with escaped quotes, comma and braces
+            ("common_arg.template_draw[0].c", (7, 33)),
+            ("common_arg.template_draw[1]", (8, 7)),
+            ("common_arg.template_draw[1][0]", (8, 8)),
+            ("common_arg.template_draw[1][1].f", (8, 15)),
+        ] {
+            assert_eq!(positions.get(path), Some(&expected), "{}", path);
+        }
+        // Nothing is found in comments
+        assert_eq!(positions.len(), 11);
+        // Nearest parent of path inside a value
+        assert_eq!(
+            position(&positions, "common_arg.template_draw[0].t.x"),
+            Some((7, 11))
+        );
+        assert_eq!(position(&positions, "other"), None);
+    }
+
+    fn draw_issues(value: Value, columns: Option<(&[String], usize)>) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        check_draw(&value, "d", 1, columns, &Variables::new(), &mut issues);
+        issues
+    }
+
+    #[test]
+    fn draw_font_and_color() {
+        let issues = draw_issues(json!([{"f": 1, "c": "ff0000"}, {"f": 2}, {"f": -1}]), None);
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["d[1].f", "d[2].f"]);
+        assert!(issues[0].message.contains("out of range (0 to 1"));
+
+        let issues = draw_issues(
+            json!([{"c": "red"}, {"fill": "{{2}}", "stroke": "12345"}]),
+            None,
+        );
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["d[0].c", "d[1].stroke"]);
+        assert!(issues.iter().all(|i| !i.warning));
+    }
+
+    #[test]
+    fn draw_out_of_canvas() {
+        let issues = draw_issues(
+            json!([{"x": 90, "w": 20, "y": 50}, {"cx": -5}, {"x2": 100}]),
+            None,
+        );
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["d[0].x", "d[1].cx"]);
+        assert!(issues.iter().all(|i| i.warning));
+        assert_eq!(
+            issues[0].message,
+            "Position 90 + size 20 is out of canvas (0 to 100)"
+        );
+    }
+
+    #[test]
+    fn draw_columns() {
+        let headers = ["name".to_string()];
+        let issues = draw_issues(
+            json!([{"if": "vip", "t": "{{name}} {{2}}"}, {"t": "{{name}}"}]),
+            Some((&headers, 1)),
+        );
+        let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, ["d[0].if", "d[0].t"]);
+        // Columns are not checked without data
+        assert!(draw_issues(json!([{"t": "{{9}}"}]), None).is_empty());
+    }
+}